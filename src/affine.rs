@@ -0,0 +1,631 @@
+// Affine gap scores charge a gap of length L as open + extend * L rather than L times
+// one gap score, so one long gap costs less than several short ones adding up to the
+// same length, which is how insertions and deletions tend to happen in practice. Gotoh's
+// recurrence handles this by tracking, alongside the best score for each pair of
+// prefixes, the best score of alignments ending in each kind of gap, so extending a gap
+// doesn't pay to open it again.
+//
+// Only one row of each is needed for the score, so that pass runs in linear space. The
+// alignment itself comes from Myers and Miller's extension of Hirschberg's algorithm:
+// split b in half, sweep forwards over the top half and backwards over the bottom one,
+// and find where the best path crosses the middle, either through a cell or in the
+// middle of a gap that spans both halves, in which case the two halves must not both
+// pay to open it. The halves are then solved recursively, told whether a gap at their
+// shared corner is already open.
+
+use crate::{Edit, EditOp, EditScript, ScoredAlignment, Scoring};
+
+// NONE is the score of an impossible state, below any real total but high enough that
+// adding gap scores to it can't overflow
+const NONE: i64 = i64::MIN / 4;
+
+/// AffineGaps scores a gap of length L as open + extend * L. Both are usually negative,
+/// with open the larger penalty of the two. Scores are added up as i64, which can't
+/// overflow as long as the two inputs add up to fewer than 2^29 symbols
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AffineGaps {
+    pub open: i32,
+    pub extend: i32,
+}
+
+impl AffineGaps {
+    // score returns the score of a gap of length len, where no gap scores nothing
+    fn score(&self, len: usize) -> i64 {
+        if len == 0 {
+            0
+        } else {
+            self.open() + self.extend() * len as i64
+        }
+    }
+
+    fn open(&self) -> i64 {
+        i64::from(self.open)
+    }
+
+    fn extend(&self) -> i64 {
+        i64::from(self.extend)
+    }
+}
+
+/// Affine_align returns the best scoring alignment of all of a against all of b,
+/// counting each char as one symbol, with symbols scored by scoring and gaps by gaps in
+/// place of scoring's own gap score. Memory is linear in the length of the shorter input
+///
+/// ```
+/// use lev_rs::{affine_align, AffineGaps, Scores};
+///
+/// let scores = Scores { matched: 2, mismatched: -2, gap: 0 };
+/// let gaps = AffineGaps { open: -3, extend: -1 };
+///
+/// // One gap of three beats three gaps of one
+/// let aligned = affine_align("the cat sat", "the sat", &scores, gaps);
+/// assert_eq!(aligned.script.alignment(
+///     &"the cat sat".chars().collect::<Vec<_>>(),
+///     &"the sat".chars().collect::<Vec<_>>(),
+/// ).b, "the---- sat");
+/// assert_eq!(aligned.score, 14 - 7);
+/// ```
+pub fn affine_align<S: Scoring<char>>(
+    a: &str,
+    b: &str,
+    scoring: &S,
+    gaps: AffineGaps,
+) -> ScoredAlignment {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    affine_align_slices(&a, &b, scoring, gaps)
+}
+
+/// Affine_align_slices returns the best scoring alignment of all of sequence a against
+/// all of sequence b, as affine_align does for strings
+///
+/// ```
+/// use lev_rs::{affine_align_slices, AffineGaps, SubstitutionMatrix};
+///
+/// let blosum62 = SubstitutionMatrix::blosum62(0);
+/// let gaps = AffineGaps { open: -10, extend: -1 };
+/// let aligned = affine_align_slices(b"HEAGAWGHEE", b"PAWHEAE", &blosum62, gaps);
+/// assert_eq!(aligned.script.cigar(), "3D7M");
+/// ```
+pub fn affine_align_slices<T, S>(a: &[T], b: &[T], scoring: &S, gaps: AffineGaps) -> ScoredAlignment
+where
+    T: PartialEq,
+    S: Scoring<T>,
+{
+    // Rows run along the first input, so make that the shorter one and flip the
+    // resulting edits back around afterwards
+    let edits = if a.len() > b.len() {
+        let score = |p: &T, q: &T| i64::from(scoring.score(q, p));
+        let mut state = State::new(b.len(), score, gaps);
+        state.split(b, a, (0, 0), (gaps.open(), gaps.open()));
+        state
+            .edits
+            .into_iter()
+            .map(|e| Edit {
+                op: match e.op {
+                    EditOp::Insert => EditOp::Delete,
+                    EditOp::Delete => EditOp::Insert,
+                    op => op,
+                },
+                a: e.b,
+                b: e.a,
+            })
+            .collect()
+    } else {
+        let score = |p: &T, q: &T| i64::from(scoring.score(p, q));
+        let mut state = State::new(a.len(), score, gaps);
+        state.split(a, b, (0, 0), (gaps.open(), gaps.open()));
+        state.edits
+    };
+
+    let script = EditScript::new(edits);
+    ScoredAlignment {
+        score: rescore(&script, a, b, scoring, gaps),
+        a_range: 0..a.len(),
+        b_range: 0..b.len(),
+        script,
+    }
+}
+
+/// Affine_score returns the score of the best alignment of all of a against all of b,
+/// counting each char as one symbol, without working out the alignment itself
+///
+/// ```
+/// use lev_rs::{affine_score, AffineGaps, Scores};
+///
+/// let gaps = AffineGaps { open: -3, extend: -1 };
+/// assert_eq!(affine_score("the cat sat", "the sat", &Scores::default(), gaps), 7 - 7);
+/// ```
+pub fn affine_score<S: Scoring<char>>(a: &str, b: &str, scoring: &S, gaps: AffineGaps) -> i64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    affine_score_slices(&a, &b, scoring, gaps)
+}
+
+/// Affine_score_slices returns the score of the best alignment of all of sequence a
+/// against all of sequence b, without working out the alignment itself. Memory is
+/// linear in the length of the shorter sequence
+pub fn affine_score_slices<T, S>(a: &[T], b: &[T], scoring: &S, gaps: AffineGaps) -> i64
+where
+    T: PartialEq,
+    S: Scoring<T>,
+{
+    let (mut scores, mut inserts) = (Vec::new(), Vec::new());
+    if a.len() > b.len() {
+        let score = |p: &T, q: &T| i64::from(scoring.score(q, p));
+        sweep(
+            &mut scores,
+            &mut inserts,
+            b.iter(),
+            a.iter(),
+            &score,
+            gaps,
+            gaps.open(),
+        );
+        scores[b.len()]
+    } else {
+        let score = |p: &T, q: &T| i64::from(scoring.score(p, q));
+        sweep(
+            &mut scores,
+            &mut inserts,
+            a.iter(),
+            b.iter(),
+            &score,
+            gaps,
+            gaps.open(),
+        );
+        scores[a.len()]
+    }
+}
+
+// sweep fills scores[x] with the best score of aligning the first x symbols of a against
+// all of b, and inserts[x] with the best such score among alignments ending in a gap in
+// a. A gap in a that starts before the first symbol of each opens with start_open
+// rather than gaps.open
+fn sweep<'t, T: 't, F>(
+    scores: &mut Vec<i64>,
+    inserts: &mut Vec<i64>,
+    a: impl ExactSizeIterator<Item = &'t T> + Clone,
+    b: impl Iterator<Item = &'t T>,
+    score: &F,
+    gaps: AffineGaps,
+    start_open: i64,
+) where
+    F: Fn(&T, &T) -> i64,
+{
+    scores.clear();
+    scores.extend((0..=a.len()).map(|x| gaps.score(x)));
+    inserts.clear();
+    inserts.resize(a.len() + 1, NONE);
+
+    for (y, q) in b.enumerate() {
+        let mut diagonal = scores[0];
+        scores[0] = start_open + gaps.extend() * (y as i64 + 1);
+        inserts[0] = scores[0];
+
+        // The best score in this row ending in a gap in b
+        let mut delete = NONE;
+        for (x, p) in a.clone().enumerate() {
+            delete = delete.max(scores[x] + gaps.open()) + gaps.extend();
+            let insert = inserts[x + 1].max(scores[x + 1] + gaps.open()) + gaps.extend();
+            let cell = (diagonal + score(p, q)).max(insert).max(delete);
+            diagonal = scores[x + 1];
+            scores[x + 1] = cell;
+            inserts[x + 1] = insert;
+        }
+    }
+}
+
+// State holds the output and the reusable row buffers for one affine_align call, with
+// score giving the score of a symbol from the shorter input against one from the longer
+struct State<F> {
+    score: F,
+    gaps: AffineGaps,
+    edits: Vec<Edit>,
+    forward: Vec<i64>,
+    forward_inserts: Vec<i64>,
+    backward: Vec<i64>,
+    backward_inserts: Vec<i64>,
+}
+
+impl<F> State<F> {
+    fn new(len: usize, score: F, gaps: AffineGaps) -> Self {
+        State {
+            score,
+            gaps,
+            edits: Vec::new(),
+            forward: Vec::with_capacity(len + 1),
+            forward_inserts: Vec::with_capacity(len + 1),
+            backward: Vec::with_capacity(len + 1),
+            backward_inserts: Vec::with_capacity(len + 1),
+        }
+    }
+
+    fn push(&mut self, op: EditOp, a: usize, b: usize) {
+        self.edits.push(Edit { op, a, b });
+    }
+
+    // split appends the edits aligning a against b, where a and b start at offsets
+    // a_start and b_start in the original inputs. A gap in a touching the top left
+    // corner opens with start_open, and one touching the bottom right with end_open,
+    // which are 0 when it carries on a gap already paid for outside
+    fn split<T: PartialEq>(
+        &mut self,
+        a: &[T],
+        b: &[T],
+        (a_start, b_start): (usize, usize),
+        (start_open, end_open): (i64, i64),
+    ) where
+        F: Fn(&T, &T) -> i64,
+    {
+        match b.len() {
+            0 => {
+                for x in 0..a.len() {
+                    self.push(EditOp::Delete, a_start + x, b_start);
+                }
+                return;
+            }
+            1 => {
+                return self.single(a, &b[0], (a_start, b_start), (start_open, end_open));
+            }
+            _ => {}
+        }
+
+        let b_mid = b.len() / 2;
+        let (b_top, b_bottom) = b.split_at(b_mid);
+        let gaps = self.gaps;
+
+        sweep(
+            &mut self.forward,
+            &mut self.forward_inserts,
+            a.iter(),
+            b_top.iter(),
+            &self.score,
+            gaps,
+            start_open,
+        );
+        sweep(
+            &mut self.backward,
+            &mut self.backward_inserts,
+            a.iter().rev(),
+            b_bottom.iter().rev(),
+            &self.score,
+            gaps,
+            end_open,
+        );
+
+        // The best path either passes through the middle at x, or is partway through a
+        // gap in a there, which both sweeps have paid to open
+        let n = a.len();
+        let mut best = (NONE, 0, false);
+        for x in 0..=n {
+            let through = self.forward[x] + self.backward[n - x];
+            if through > best.0 {
+                best = (through, x, false);
+            }
+            let spanning = self.forward_inserts[x] + self.backward_inserts[n - x] - gaps.open();
+            if spanning > best.0 {
+                best = (spanning, x, true);
+            }
+        }
+
+        let (_, x, spanning) = best;
+        if spanning {
+            // The gap takes in the last symbol of the top half and the first of the
+            // bottom, and carries on into both halves for free
+            let (top, bottom) = (&b[..b_mid - 1], &b[b_mid + 1..]);
+            self.split(&a[..x], top, (a_start, b_start), (start_open, 0));
+            self.push(EditOp::Insert, a_start + x, b_start + b_mid - 1);
+            self.push(EditOp::Insert, a_start + x, b_start + b_mid);
+            let bottom_start = (a_start + x, b_start + b_mid + 1);
+            self.split(&a[x..], bottom, bottom_start, (0, end_open));
+        } else {
+            self.split(
+                &a[..x],
+                b_top,
+                (a_start, b_start),
+                (start_open, gaps.open()),
+            );
+            let bottom_start = (a_start + x, b_start + b_mid);
+            self.split(&a[x..], b_bottom, bottom_start, (gaps.open(), end_open));
+        }
+    }
+
+    // single appends the edits aligning a against the single symbol q. A gap in a
+    // opens with start_open before a and end_open after it
+    fn single<T: PartialEq>(
+        &mut self,
+        a: &[T],
+        q: &T,
+        (a_start, b_start): (usize, usize),
+        (start_open, end_open): (i64, i64),
+    ) where
+        F: Fn(&T, &T) -> i64,
+    {
+        let gaps = self.gaps;
+        let n = a.len();
+
+        // Either q lines up with some symbol of a and the rest of a is deleted around it,
+        // or q is inserted at whichever end is cheaper and all of a deleted
+        let inserted = start_open.max(end_open) + gaps.extend() + gaps.score(n);
+        let lined_up = a
+            .iter()
+            .enumerate()
+            .map(|(x, p)| {
+                (
+                    gaps.score(x) + (self.score)(p, q) + gaps.score(n - x - 1),
+                    x,
+                )
+            })
+            .rev()
+            .max_by_key(|&(score, _)| score)
+            .filter(|&(score, _)| score >= inserted);
+
+        match lined_up {
+            Some((_, at)) => {
+                for x in 0..at {
+                    self.push(EditOp::Delete, a_start + x, b_start);
+                }
+                let op = if &a[at] == q {
+                    EditOp::Match
+                } else {
+                    EditOp::Substitute
+                };
+                self.push(op, a_start + at, b_start);
+                for x in at + 1..n {
+                    self.push(EditOp::Delete, a_start + x, b_start + 1);
+                }
+            }
+            None if end_open > start_open => {
+                for x in 0..n {
+                    self.push(EditOp::Delete, a_start + x, b_start);
+                }
+                self.push(EditOp::Insert, a_start + n, b_start);
+            }
+            None => {
+                self.push(EditOp::Insert, a_start, b_start);
+                for x in 0..n {
+                    self.push(EditOp::Delete, a_start + x, b_start + 1);
+                }
+            }
+        }
+    }
+}
+
+// rescore adds up the score of script as an alignment of a against b
+fn rescore<T, S: Scoring<T>>(
+    script: &EditScript,
+    a: &[T],
+    b: &[T],
+    scoring: &S,
+    gaps: AffineGaps,
+) -> i64 {
+    let mut score = 0;
+    let mut last = None;
+    for edit in script.iter() {
+        score += match edit.op {
+            EditOp::Match | EditOp::Substitute => i64::from(scoring.score(&a[edit.a], &b[edit.b])),
+            op if last == Some(op) => gaps.extend(),
+            _ => gaps.open() + gaps.extend(),
+        };
+        last = Some(edit.op);
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Scores, SubstitutionMatrix};
+
+    // brute_force tries every alignment of a against b, returning the best score
+    fn brute_force(a: &[char], b: &[char], scores: &Scores, gaps: AffineGaps) -> i64 {
+        // last is the kind of gap the alignment so far ends in, if any
+        fn best(a: &[char], b: &[char], s: &Scores, g: AffineGaps, last: Option<EditOp>) -> i64 {
+            let gap = |op| {
+                if last == Some(op) {
+                    g.extend()
+                } else {
+                    g.open() + g.extend()
+                }
+            };
+            let mut found = if a.is_empty() && b.is_empty() {
+                0
+            } else {
+                NONE
+            };
+            if let (Some(p), Some(q)) = (a.first(), b.first()) {
+                let matched = i64::from(Scoring::score(s, p, q));
+                found = found.max(matched + best(&a[1..], &b[1..], s, g, None));
+            }
+            if !a.is_empty() {
+                let delete = EditOp::Delete;
+                found = found.max(gap(delete) + best(&a[1..], b, s, g, Some(delete)));
+            }
+            if !b.is_empty() {
+                let insert = EditOp::Insert;
+                found = found.max(gap(insert) + best(a, &b[1..], s, g, Some(insert)));
+            }
+            found
+        }
+        best(a, b, scores, gaps, None)
+    }
+
+    // check replays script, making sure it aligns all of a against all of b in order
+    fn check(script: &EditScript, a: &[char], b: &[char]) {
+        let (mut x, mut y) = (0, 0);
+        for edit in script {
+            assert_eq!((edit.a, edit.b), (x, y), "edit {:?} out of place", edit);
+            match edit.op {
+                EditOp::Match | EditOp::Substitute => {
+                    assert_eq!(edit.op == EditOp::Match, a[x] == b[y]);
+                    (x, y) = (x + 1, y + 1);
+                }
+                EditOp::Insert => y += 1,
+                EditOp::Delete => x += 1,
+                EditOp::Transpose => unreachable!("affine_align never transposes"),
+            }
+        }
+        assert_eq!((x, y), (a.len(), b.len()));
+    }
+
+    #[test]
+    fn it_agrees_with_brute_force() {
+        let words = [
+            "", "a", "ab", "ba", "abc", "fast", "past", "abcab", "cabba", "xaby",
+        ];
+        let scoring = [
+            (
+                Scores {
+                    matched: 1,
+                    mismatched: -1,
+                    gap: 0,
+                },
+                AffineGaps {
+                    open: -2,
+                    extend: -1,
+                },
+            ),
+            (
+                Scores {
+                    matched: 2,
+                    mismatched: -3,
+                    gap: 0,
+                },
+                AffineGaps {
+                    open: -3,
+                    extend: -1,
+                },
+            ),
+            (
+                Scores {
+                    matched: 5,
+                    mismatched: -4,
+                    gap: 0,
+                },
+                AffineGaps {
+                    open: -10,
+                    extend: 0,
+                },
+            ),
+            (
+                Scores {
+                    matched: 0,
+                    mismatched: -1,
+                    gap: 0,
+                },
+                AffineGaps {
+                    open: 0,
+                    extend: -1,
+                },
+            ),
+        ];
+
+        for (scores, gaps) in scoring.iter() {
+            for a in words.iter() {
+                for b in words.iter() {
+                    let a_chars: Vec<char> = a.chars().collect();
+                    let b_chars: Vec<char> = b.chars().collect();
+                    let want = brute_force(&a_chars, &b_chars, scores, *gaps);
+
+                    let got = affine_score(a, b, scores, *gaps);
+                    assert_eq!(
+                        got, want,
+                        "affine_score({}, {}, {:?}, {:?}) - got {}, want {}",
+                        a, b, scores, gaps, got, want
+                    );
+
+                    let aligned = affine_align(a, b, scores, *gaps);
+                    assert_eq!(
+                        aligned.score, want,
+                        "affine_align({}, {}, {:?}, {:?}) - got {:?}, want {}",
+                        a, b, scores, gaps, aligned, want
+                    );
+                    check(&aligned.script, &a_chars, &b_chars);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_aligns_long_inputs() {
+        // Long enough for several levels of splitting, with gaps spanning the splits
+        let a = "the quick brown fox jumps over the lazy dog and then takes a long nap";
+        let b = "the quick fox jumps right over the dog and takes a very long nap in the sun";
+        let scores = Scores {
+            matched: 3,
+            mismatched: -2,
+            gap: 0,
+        };
+
+        for gaps in [
+            AffineGaps {
+                open: -5,
+                extend: -1,
+            },
+            AffineGaps {
+                open: -1,
+                extend: -2,
+            },
+            AffineGaps {
+                open: -20,
+                extend: 0,
+            },
+        ] {
+            for (a, b) in [(a, b), (b, a)] {
+                let aligned = affine_align(a, b, &scores, gaps);
+                let want = affine_score(a, b, &scores, gaps);
+                assert_eq!(
+                    aligned.score, want,
+                    "affine_align({}, {}, {:?})",
+                    a, b, gaps
+                );
+                check(
+                    &aligned.script,
+                    &a.chars().collect::<Vec<_>>(),
+                    &b.chars().collect::<Vec<_>>(),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_totals_scores_past_i32() {
+        let scores = Scores {
+            matched: i32::MAX,
+            mismatched: i32::MIN,
+            gap: 0,
+        };
+        let gaps = AffineGaps {
+            open: i32::MIN,
+            extend: i32::MIN,
+        };
+
+        let a = "ab".repeat(500);
+        let b = format!("{}{}", a, "x".repeat(10));
+        let want = 1000 * i64::from(i32::MAX) + gaps.score(10);
+        assert_eq!(affine_score(&a, &b, &scores, gaps), want);
+        assert_eq!(affine_align(&a, &b, &scores, gaps).score, want);
+    }
+
+    #[test]
+    fn it_aligns_sequences() {
+        let blosum62 = SubstitutionMatrix::blosum62(0);
+        let gaps = AffineGaps {
+            open: -11,
+            extend: -1,
+        };
+        let a = b"MKTAYIAKQRQISFVKSHFSRQLEERLGLIEVQ";
+        let b = b"MKTAYIAKQRQISFVKSHFSRQDILDLWIYHTQGYFPD";
+
+        let aligned = affine_align_slices(a, b, &blosum62, gaps);
+        assert_eq!(aligned.score, affine_score_slices(a, b, &blosum62, gaps));
+        assert_eq!(
+            aligned.script.edits()[..22]
+                .iter()
+                .filter(|e| e.op == EditOp::Match)
+                .count(),
+            22
+        );
+    }
+}
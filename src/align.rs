@@ -0,0 +1,488 @@
+// Scored alignment turns the edit distance around: rather than counting the edits between
+// a and b, it adds up a score for each column of an alignment, positive for symbols that
+// belong together and negative for those that don't or for gaps, and finds the alignment
+// with the highest total. Which gaps at the ends of the inputs count against the score is
+// up to an AlignMode, so the same DP answers how well a fits as a prefix of b, as a suffix,
+// anywhere inside it, or overlapping its start.
+//
+// Local alignment, from Smith and Waterman, goes further and lets both inputs start and
+// end anywhere: no cell of the DP goes below 0, since an alignment that has scored less
+// than nothing so far is better off starting over, and the best cell anywhere is where
+// the best alignment ends.
+
+use std::ops::Range;
+
+use crate::edit::traceback;
+use crate::{EditOp, EditScript, Matrix};
+
+/// Scoring assigns scores to the columns of an alignment of symbols of type T. Alignments
+/// are chosen to maximize the total score, so pairs of symbols that belong together
+/// should score above zero, and gaps below it
+///
+/// ```
+/// use lev_rs::{semiglobal_align, AlignMode, Scoring};
+///
+/// // Letters match regardless of case
+/// struct CaseInsensitive;
+///
+/// impl Scoring<char> for CaseInsensitive {
+///     fn score(&self, a: &char, b: &char) -> i32 {
+///         if a.eq_ignore_ascii_case(b) { 2 } else { -1 }
+///     }
+///
+///     fn gap(&self) -> i32 {
+///         -2
+///     }
+/// }
+///
+/// let aligned = semiglobal_align("Cat", "cAT", AlignMode::Global, &CaseInsensitive);
+/// assert_eq!(aligned.score, 6);
+/// ```
+pub trait Scoring<T: ?Sized> {
+    /// Score returns the score of aligning a against b
+    fn score(&self, a: &T, b: &T) -> i32;
+
+    /// Gap returns the score of aligning a symbol against a gap
+    fn gap(&self) -> i32;
+}
+
+/// Scores gives every match, mismatch and gap a fixed score. The default scores a match
+/// 1, and a mismatch or a gap -1
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Scores {
+    pub matched: i32,
+    pub mismatched: i32,
+    pub gap: i32,
+}
+
+impl Default for Scores {
+    fn default() -> Self {
+        Scores {
+            matched: 1,
+            mismatched: -1,
+            gap: -1,
+        }
+    }
+}
+
+impl<T: PartialEq + ?Sized> Scoring<T> for Scores {
+    fn score(&self, a: &T, b: &T) -> i32 {
+        if a == b {
+            self.matched
+        } else {
+            self.mismatched
+        }
+    }
+
+    fn gap(&self) -> i32 {
+        self.gap
+    }
+}
+
+/// ScoredAlignment is the best alignment found between `a[a_range]` and
+/// `b[b_range]`, with its total score. The edits in script hold offsets into the whole of
+/// a and b, so script.alignment(a, b) lines up just the aligned parts.
+///
+/// Each column scores an i32 but the total is an i64, which can't overflow however the
+/// columns are scored as long as a and b add up to fewer than 2^29 symbols
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScoredAlignment {
+    pub score: i64,
+    pub a_range: Range<usize>,
+    pub b_range: Range<usize>,
+    pub script: EditScript,
+}
+
+/// AlignMode chooses which gaps at the ends of a and b are free, so they neither add to
+/// nor take away from the score
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AlignMode {
+    /// Every gap counts, so all of a is aligned against all of b
+    #[default]
+    Global,
+    /// Gaps after the end of a are free: how well a fits as a prefix of b
+    PrefixOf,
+    /// Gaps before the start of a are free: how well a fits as a suffix of b
+    SuffixOf,
+    /// Gaps before and after a are free: how well a fits anywhere inside b
+    Within,
+    /// Gaps before b and after a are free: how well the end of a overlaps the start of
+    /// b. Swap the inputs for the end of b overlapping the start of a
+    Overlap,
+}
+
+impl AlignMode {
+    // free_ends returns whether leading a, leading b, trailing a and trailing b symbols
+    // can be skipped for free
+    fn free_ends(self) -> (bool, bool, bool, bool) {
+        match self {
+            AlignMode::Global => (false, false, false, false),
+            AlignMode::PrefixOf => (false, false, false, true),
+            AlignMode::SuffixOf => (false, true, false, false),
+            AlignMode::Within => (false, true, false, true),
+            AlignMode::Overlap => (true, false, false, true),
+        }
+    }
+}
+
+/// Semiglobal_align returns the best scoring alignment of a and b, counting each char as
+/// one symbol, with gaps at the ends free as chosen by mode
+///
+/// ```
+/// use lev_rs::{semiglobal_align, AlignMode, Scores};
+///
+/// let scores = Scores::default();
+///
+/// // "plan" is most of the way to being a prefix of "planetary"
+/// let aligned = semiglobal_align("plank", "planetary", AlignMode::PrefixOf, &scores);
+/// assert_eq!((aligned.score, aligned.b_range), (3, 0..5));
+///
+/// // The end of "rainbow" overlaps the start of "bowtie"
+/// let aligned = semiglobal_align("rainbow", "bowtie", AlignMode::Overlap, &scores);
+/// assert_eq!((aligned.a_range, aligned.b_range), (4..7, 0..3));
+/// ```
+pub fn semiglobal_align<S: Scoring<char>>(
+    a: &str,
+    b: &str,
+    mode: AlignMode,
+    scoring: &S,
+) -> ScoredAlignment {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    semiglobal_align_slices(&a, &b, mode, scoring)
+}
+
+/// Semiglobal_align_slices returns the best scoring alignment of sequences a and b, with
+/// gaps at the ends free as chosen by mode
+pub fn semiglobal_align_slices<T, S>(
+    a: &[T],
+    b: &[T],
+    mode: AlignMode,
+    scoring: &S,
+) -> ScoredAlignment
+where
+    T: PartialEq,
+    S: Scoring<T>,
+{
+    let (lead_a, lead_b, trail_a, trail_b) = mode.free_ends();
+    let matrix = score_matrix(a, b, scoring, lead_a, lead_b, false);
+
+    // Free trailing symbols mean the alignment can end anywhere along the bottom row or
+    // the right hand column. Prefer the corner, then the longest alignment
+    let mut end = (a.len(), b.len());
+    if trail_a {
+        for x in (0..a.len()).rev() {
+            if matrix.get(x, b.len()) > matrix.get(end.0, end.1) {
+                end = (x, b.len());
+            }
+        }
+    }
+    if trail_b {
+        for y in (0..b.len()).rev() {
+            if matrix.get(a.len(), y) > matrix.get(end.0, end.1) {
+                end = (a.len(), y);
+            }
+        }
+    }
+
+    trace(&matrix, a, b, scoring, end, |x, y| {
+        (y == 0 && (x == 0 || lead_a)) || (x == 0 && lead_b)
+    })
+}
+
+/// Local_align returns the best scoring alignment between any part of a and any part of
+/// b, counting each char as one symbol, as found by the Smith-Waterman algorithm. If no
+/// pair of symbols scores above zero the alignment is empty, with a score of 0
+///
+/// ```
+/// use lev_rs::{local_align, Scores};
+///
+/// let scores = Scores { matched: 2, mismatched: -1, gap: -2 };
+/// let aligned = local_align("xxGATTACAxx", "yyyGATCACA", &scores);
+/// assert_eq!((aligned.score, aligned.a_range, aligned.b_range), (11, 2..9, 3..10));
+/// assert_eq!(aligned.script.cigar(), "7M");
+/// ```
+pub fn local_align<S: Scoring<char>>(a: &str, b: &str, scoring: &S) -> ScoredAlignment {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    local_align_slices(&a, &b, scoring)
+}
+
+/// Local_align_slices returns the best scoring alignment between any part of sequence a
+/// and any part of sequence b, as local_align does for strings
+pub fn local_align_slices<T, S>(a: &[T], b: &[T], scoring: &S) -> ScoredAlignment
+where
+    T: PartialEq,
+    S: Scoring<T>,
+{
+    let matrix = score_matrix(a, b, scoring, true, true, true);
+
+    // The alignment ends at the best cell anywhere, taking the first in column order
+    let mut end = (0, 0);
+    for y in 0..=b.len() {
+        for x in 0..=a.len() {
+            if matrix.get(x, y) > matrix.get(end.0, end.1) {
+                end = (x, y);
+            }
+        }
+    }
+
+    // A cell of 0 is where the alignment can start for free
+    trace(&matrix, a, b, scoring, end, |x, y| matrix.get(x, y) == 0)
+}
+
+// score_matrix fills the same grid as levenshtein_matrix with the best score of aligning
+// each pair of prefixes rather than their distance. lead_a and lead_b make skipping the
+// start of each input free, and local also lets an alignment start at any cell, so no
+// cell goes below 0
+fn score_matrix<T, S>(
+    a: &[T],
+    b: &[T],
+    scoring: &S,
+    lead_a: bool,
+    lead_b: bool,
+    local: bool,
+) -> Matrix<i64>
+where
+    T: PartialEq,
+    S: Scoring<T>,
+{
+    let gap = i64::from(scoring.gap());
+    let floor = if local { 0 } else { i64::MIN };
+
+    let mut matrix = Matrix::new(a.len() + 1, b.len() + 1, 0);
+    for x in 1..=a.len() {
+        if !lead_a {
+            matrix.set(x, 0, matrix.get(x - 1, 0) + gap);
+        }
+    }
+    for y in 1..=b.len() {
+        if !lead_b {
+            matrix.set(0, y, matrix.get(0, y - 1) + gap);
+        }
+    }
+    for y in 1..=b.len() {
+        for x in 1..=a.len() {
+            let diagonal =
+                matrix.get(x - 1, y - 1) + i64::from(scoring.score(&a[x - 1], &b[y - 1]));
+            let delete = matrix.get(x - 1, y) + gap;
+            let insert = matrix.get(x, y - 1) + gap;
+            matrix.set(x, y, diagonal.max(delete).max(insert).max(floor));
+        }
+    }
+    matrix
+}
+
+// trace follows a matrix from score_matrix back from end until done, returning the
+// alignment it passes through
+fn trace<T, S>(
+    matrix: &Matrix<i64>,
+    a: &[T],
+    b: &[T],
+    scoring: &S,
+    end: (usize, usize),
+    done: impl Fn(usize, usize) -> bool,
+) -> ScoredAlignment
+where
+    T: PartialEq,
+    S: Scoring<T>,
+{
+    let gap = i64::from(scoring.gap());
+    let script = traceback(end.0, end.1, done, |x, y| {
+        let cell = matrix.get(x, y);
+        if x > 0 && y > 0 {
+            let diagonal = matrix.get(x - 1, y - 1);
+            if diagonal + i64::from(scoring.score(&a[x - 1], &b[y - 1])) == cell {
+                return if a[x - 1] == b[y - 1] {
+                    EditOp::Match
+                } else {
+                    EditOp::Substitute
+                };
+            }
+        }
+        if x > 0 && matrix.get(x - 1, y) + gap == cell {
+            EditOp::Delete
+        } else {
+            EditOp::Insert
+        }
+    });
+
+    let (start_a, start_b) = script.edits().first().map_or(end, |e| (e.a, e.b));
+    ScoredAlignment {
+        score: matrix.get(end.0, end.1),
+        a_range: start_a..end.0,
+        b_range: start_b..end.1,
+        script,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_aligns_in_every_mode() {
+        use AlignMode::*;
+
+        // a, b, mode, score, a range, b range
+        let test_cases = [
+            ("", "", Global, 0, 0..0, 0..0),
+            ("abc", "", Global, -3, 0..3, 0..0),
+            ("kitten", "sitting", Global, 1, 0..6, 0..7),
+            ("plan", "planetary", Global, -1, 0..4, 0..9),
+            ("plan", "planetary", PrefixOf, 4, 0..4, 0..4),
+            ("plan", "explanation", PrefixOf, 2, 0..4, 0..6),
+            ("tary", "planetary", SuffixOf, 4, 0..4, 5..9),
+            ("plan", "planetary", SuffixOf, -1, 0..4, 0..9),
+            ("net", "planetary", Within, 3, 0..3, 3..6),
+            ("nxt", "planetary", Within, 1, 0..3, 3..6),
+            ("rainbow", "bowtie", Overlap, 3, 4..7, 0..3),
+            ("abc", "xyz", Overlap, 0, 3..3, 0..0),
+        ];
+
+        for tc in test_cases.iter() {
+            let got = semiglobal_align(tc.0, tc.1, tc.2, &Scores::default());
+            assert_eq!(
+                (got.score, got.a_range.clone(), got.b_range.clone()),
+                (tc.3, tc.4.clone(), tc.5.clone()),
+                "semiglobal_align({}, {}, {:?}) - got {:?}",
+                tc.0,
+                tc.1,
+                tc.2,
+                got
+            );
+
+            // Rescoring the script gives the same total, and it covers exactly the ranges
+            let a: Vec<char> = tc.0.chars().collect();
+            let b: Vec<char> = tc.1.chars().collect();
+            let mut score = 0;
+            let (mut x, mut y) = (got.a_range.start, got.b_range.start);
+            for edit in got.script.iter() {
+                assert_eq!(
+                    (edit.a, edit.b),
+                    (x, y),
+                    "semiglobal_align({}, {}, {:?})",
+                    tc.0,
+                    tc.1,
+                    tc.2
+                );
+                match edit.op {
+                    EditOp::Match | EditOp::Substitute => {
+                        score += i64::from(Scores::default().score(&a[x], &b[y]));
+                        x += 1;
+                        y += 1;
+                    }
+                    EditOp::Delete => {
+                        score -= 1;
+                        x += 1;
+                    }
+                    EditOp::Insert => {
+                        score -= 1;
+                        y += 1;
+                    }
+                    EditOp::Transpose => unreachable!("alignments don't transpose"),
+                }
+            }
+            assert_eq!((score, x, y), (got.score, got.a_range.end, got.b_range.end));
+        }
+    }
+
+    #[test]
+    fn it_matches_distance_when_global() {
+        // With a match worth nothing and everything else -1, the best score is minus the
+        // Levenshtein distance
+        let scores = Scores {
+            matched: 0,
+            mismatched: -1,
+            gap: -1,
+        };
+        let words = [
+            "", "a", "fast", "past", "kitten", "sitting", "ababab", "bababa",
+        ];
+
+        for a in words.iter() {
+            for b in words.iter() {
+                let got = semiglobal_align(a, b, AlignMode::Global, &scores);
+                assert_eq!(
+                    -got.score as usize,
+                    crate::distance(a, b),
+                    "semiglobal_align({}, {})",
+                    a,
+                    b
+                );
+                assert_eq!(got.script.distance(), crate::distance(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn it_totals_scores_past_i32() {
+        let scores = Scores {
+            matched: i32::MAX,
+            mismatched: i32::MIN,
+            gap: i32::MIN,
+        };
+        let a = "a".repeat(100);
+        let b = format!("{}bb", a);
+
+        let global = semiglobal_align(&a, &b, AlignMode::Global, &scores);
+        assert_eq!(
+            global.score,
+            100 * i64::from(i32::MAX) + 2 * i64::from(i32::MIN)
+        );
+
+        let local = local_align(&a, &b, &scores);
+        assert_eq!(local.score, 100 * i64::from(i32::MAX));
+    }
+
+    #[test]
+    fn it_aligns_locally() {
+        let scores = Scores {
+            matched: 2,
+            mismatched: -1,
+            gap: -2,
+        };
+        let words = [
+            "", "a", "abc", "xabcx", "kitten", "sitting", "GATTACA", "TACCAG", "ababab",
+        ];
+
+        for a in words.iter() {
+            for b in words.iter() {
+                let got = local_align(a, b, &scores);
+                let a: Vec<char> = a.chars().collect();
+                let b: Vec<char> = b.chars().collect();
+
+                // The best local alignment is the best global one between any parts
+                let mut want = 0;
+                for i in 0..=a.len() {
+                    for j in i..=a.len() {
+                        for k in 0..=b.len() {
+                            for l in k..=b.len() {
+                                let global = semiglobal_align_slices(
+                                    &a[i..j],
+                                    &b[k..l],
+                                    AlignMode::Global,
+                                    &scores,
+                                );
+                                want = want.max(global.score);
+                            }
+                        }
+                    }
+                }
+                assert_eq!(
+                    got.score, want,
+                    "local_align({:?}, {:?}) - got {}, want {}",
+                    a, b, got.score, want
+                );
+
+                let parts = semiglobal_align_slices(
+                    &a[got.a_range.clone()],
+                    &b[got.b_range.clone()],
+                    AlignMode::Global,
+                    &scores,
+                );
+                assert_eq!(parts.score, got.score, "local_align({:?}, {:?})", a, b);
+            }
+        }
+    }
+}
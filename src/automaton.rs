@@ -0,0 +1,340 @@
+// A Levenshtein automaton for a query and a bound k is a DFA accepting exactly the words
+// within k edits of the query, as described by Schulz and Mihov. Feeding it a word one
+// char at a time costs a table lookup per char, and because a state depends only on the
+// prefix read so far, callers walking sorted keys or a trie can share the states of a
+// common prefix and abandon a whole branch the moment it reaches a dead state.
+//
+// Rather than Schulz and Mihov's precomputed parametric tables, the DFA is built by
+// determinizing on the fly: a state is the final row of the single_row_distance sweep
+// with the query as a and the prefix read so far as b, with every entry over k clipped to
+// k + 1. Two prefixes with the same clipped row accept the same suffixes, so the rows
+// reachable from the first one are exactly the states of the DFA. Every char that doesn't
+// appear in the query moves each state the same way, so the table only needs a column
+// for each distinct query char plus one for all the others.
+//
+// A full row is as long as the query, but after j chars only the entries within k of j
+// can be k or less, since the distance is at least the difference in lengths. So, as in
+// Schulz and Mihov's normalized positions, a state is stored as the offset of its first
+// entry that is k or less along with the 2k + 1 entries from there on; everything outside
+// that band is k + 1. Building the automaton then takes O(k) per state rather than
+// O(len(query)), however long the query is.
+
+use std::cmp;
+use std::collections::{HashMap, VecDeque};
+
+use crate::min3;
+
+/// LevenshteinAutomaton is a DFA accepting the words within max edits of a query,
+/// counting each char as one symbol. States are numbered from 0, which is the start
+///
+/// ```
+/// use lev_rs::LevenshteinAutomaton;
+///
+/// let automaton = LevenshteinAutomaton::new("kitten", 2);
+/// assert_eq!(automaton.distance("sitten"), Some(1));
+/// assert_eq!(automaton.distance("sitting"), None);
+///
+/// // Walk a word by hand, stopping as soon as no continuation can match
+/// let mut state = Some(automaton.start());
+/// for c in "kitchen".chars() {
+///     state = state.and_then(|s| automaton.next(s, c));
+/// }
+/// assert_eq!(state.and_then(|s| automaton.accepts(s)), Some(2));
+/// ```
+pub struct LevenshteinAutomaton {
+    max: usize,
+    // The distinct chars of the query, sorted, which label the columns of next
+    alphabet: Vec<char>,
+    // next[state * (alphabet.len() + 1) + column] is where state goes on that column's
+    // char, with the last column for chars not in the query
+    next: Vec<Option<usize>>,
+    // accepts[state] is the distance between the query and any word ending in state, if
+    // it is at most max
+    accepts: Vec<Option<usize>>,
+}
+
+impl LevenshteinAutomaton {
+    pub fn new(query: &str, max: usize) -> Self {
+        let query: Vec<char> = query.chars().collect();
+        let mut alphabet = query.clone();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+
+        let over = max + 1;
+        let columns = alphabet.len() + 1;
+        let start = Band {
+            offset: 0,
+            values: (0..2 * max + 1)
+                .map(|x| if x <= query.len() { x } else { over })
+                .collect(),
+        };
+
+        // A char outside the query matches nothing, which the NUL char stands in for unless
+        // the query contains one too
+        let other = (0..)
+            .map(|c| char::from_u32(c).expect("small values are chars"))
+            .find(|c| alphabet.binary_search(c).is_err())
+            .expect("the query can't contain every char");
+
+        let mut ids = HashMap::from([(start.clone(), 0)]);
+        let mut next = Vec::new();
+        let mut accepts = Vec::new();
+
+        // States are numbered in the order they're found, so taking them off the front of
+        // the queue expands them in order of their ids, and each band is dropped once its
+        // transitions are known
+        let mut queue = VecDeque::from([start]);
+        while let Some(band) = queue.pop_front() {
+            accepts.push(Some(band.get(query.len(), over)).filter(|&d| d <= max));
+
+            for &c in alphabet.iter().chain(std::iter::once(&other)) {
+                let Some(stepped) = band.step(&query, c, over) else {
+                    next.push(None);
+                    continue;
+                };
+
+                let id = match ids.get(&stepped) {
+                    Some(&id) => id,
+                    None => {
+                        let id = ids.len();
+                        ids.insert(stepped.clone(), id);
+                        queue.push_back(stepped);
+                        id
+                    }
+                };
+                next.push(Some(id));
+            }
+        }
+        debug_assert_eq!(next.len(), accepts.len() * columns);
+
+        LevenshteinAutomaton {
+            max,
+            alphabet,
+            next,
+            accepts,
+        }
+    }
+
+    /// Max returns the number of edits this automaton allows
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Start returns the state before any chars have been read
+    pub fn start(&self) -> usize {
+        0
+    }
+
+    /// State_count returns the number of states in the automaton, not counting the dead
+    /// state that next reports as None
+    pub fn state_count(&self) -> usize {
+        self.accepts.len()
+    }
+
+    /// Next returns the state reached from state by reading c, or None if no word
+    /// starting with what's been read so far is within max edits of the query
+    pub fn next(&self, state: usize, c: char) -> Option<usize> {
+        let column = self
+            .alphabet
+            .binary_search(&c)
+            .unwrap_or(self.alphabet.len());
+        self.next[state * (self.alphabet.len() + 1) + column]
+    }
+
+    /// Accepts returns the distance between the query and any word that ends in state, if
+    /// it is at most max, or None otherwise
+    pub fn accepts(&self, state: usize) -> Option<usize> {
+        self.accepts[state]
+    }
+
+    /// Transitions returns the chars with a transition of their own out of state, along
+    /// with where they lead, followed by where every other char leads as (None, next)
+    pub fn transitions(
+        &self,
+        state: usize,
+    ) -> impl Iterator<Item = (Option<char>, Option<usize>)> + '_ {
+        let columns = self.alphabet.len() + 1;
+        let labels = self.alphabet.iter().map(|&c| Some(c)).chain([None]);
+        labels.zip(
+            self.next[state * columns..(state + 1) * columns]
+                .iter()
+                .copied(),
+        )
+    }
+
+    /// Distance returns the Levenshtein distance between the query and word if it is at
+    /// most max, or None otherwise
+    pub fn distance(&self, word: &str) -> Option<usize> {
+        let mut state = self.start();
+        for c in word.chars() {
+            state = self.next(state, c)?;
+        }
+        self.accepts(state)
+    }
+}
+
+// Band is a clipped row of the single_row_distance sweep, keeping only the 2k + 1
+// entries starting at offset. Every entry outside them is k + 1
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Band {
+    offset: usize,
+    values: Vec<usize>,
+}
+
+impl Band {
+    // get returns entry x of the full row
+    fn get(&self, x: usize, over: usize) -> usize {
+        x.checked_sub(self.offset)
+            .and_then(|i| self.values.get(i))
+            .copied()
+            .unwrap_or(over)
+    }
+
+    // step advances the row by the char c, as single_row_step does for a full row, or
+    // returns None if every entry of the next row is over
+    fn step(&self, query: &[char], c: char, over: usize) -> Option<Band> {
+        // Entries before offset stay over, and the next row can reach at most one entry
+        // further than this one
+        let width = self.values.len();
+        let last = cmp::min(self.offset + width, query.len());
+        let mut row = Vec::with_capacity(width + 1);
+        for x in self.offset..=last {
+            let above = self.get(x, over) + 1;
+            let cell = match x.checked_sub(1) {
+                Some(prev) => {
+                    let diagonal = self.get(prev, over) + usize::from(query[prev] != c);
+                    let left = row.last().map_or(over, |&d| d) + 1;
+                    min3(diagonal, above, left)
+                }
+                None => above,
+            };
+            row.push(cmp::min(cell, over));
+        }
+
+        let first = row.iter().position(|&d| d < over)?;
+        let mut values = row.split_off(first);
+        values.resize(width, over);
+        Some(Band {
+            offset: self.offset + first,
+            values,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: [&str; 16] = [
+        "",
+        "a",
+        "ab",
+        "ba",
+        "fast",
+        "past",
+        "foo",
+        "bar",
+        "kitten",
+        "sitting",
+        "mitten",
+        "kitchen",
+        "intention",
+        "execution",
+        "naïve",
+        "naive",
+    ];
+
+    #[test]
+    fn it_agrees_with_distance() {
+        for query in WORDS.iter() {
+            for max in 0..=3 {
+                let automaton = LevenshteinAutomaton::new(query, max);
+                for word in WORDS.iter() {
+                    let want = crate::distance_within(query, word, max);
+                    let got = automaton.distance(word);
+                    assert_eq!(
+                        got, want,
+                        "LevenshteinAutomaton::new({}, {}).distance({}) - got {:?}, want {:?}",
+                        query, max, word, got, want
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_walks_sorted_keys() {
+        let mut keys = WORDS.to_vec();
+        keys.extend(["kit", "kite", "kitsch", "kittens", "knitting", "mittens"]);
+        keys.sort();
+
+        let automaton = LevenshteinAutomaton::new("kitten", 2);
+
+        // states[i] is the state after the first i chars of the previous key, so keys
+        // sharing a prefix with it pick up where it left off
+        let mut prev: Vec<char> = Vec::new();
+        let mut states = vec![Some(automaton.start())];
+        let mut found = Vec::new();
+        for key in keys.iter() {
+            let chars: Vec<char> = key.chars().collect();
+            let shared = prev.iter().zip(&chars).take_while(|(p, c)| p == c).count();
+            states.truncate(shared + 1);
+            for &c in &chars[shared..] {
+                let state = states[states.len() - 1];
+                states.push(state.and_then(|s| automaton.next(s, c)));
+            }
+            if let Some(d) = states[chars.len()].and_then(|s| automaton.accepts(s)) {
+                found.push((*key, d));
+            }
+            prev = chars;
+        }
+
+        let want: Vec<(&str, usize)> = keys
+            .iter()
+            .filter_map(|k| crate::distance_within("kitten", k, 2).map(|d| (*k, d)))
+            .collect();
+        assert_eq!(found, want);
+    }
+
+    #[test]
+    fn it_handles_long_queries() {
+        // Long enough that the band of each state is a small part of the row
+        let query = "the quick brown fox jumps over the lazy dog, then naps in the afternoon sun";
+        let words = [
+            "the quick brown fox jumps over the lazy dog, then naps in the afternoon sun",
+            "the quick brown fox jumped over the lazy dog, then napped in the afternoon sun",
+            "teh quick brown fox jumps over the lazy dog, then naps in the afternoon sun",
+            "the quick brown fox jumps over the lazy dog then naps in the afternon sun.",
+            "a quick brown fox jumps over a lazy dog, then naps in an afternoon sun",
+            "the quick brown fox",
+            "",
+        ];
+
+        for max in 0..=4 {
+            let automaton = LevenshteinAutomaton::new(query, max);
+            for word in words.iter() {
+                let want = crate::distance_within(query, word, max);
+                let got = automaton.distance(word);
+                assert_eq!(
+                    got, want,
+                    "LevenshteinAutomaton::new({}, {}).distance({}) - got {:?}, want {:?}",
+                    query, max, word, got, want
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_exposes_transitions() {
+        let automaton = LevenshteinAutomaton::new("ab", 0);
+        assert_eq!(automaton.state_count(), 3);
+
+        let start: Vec<(Option<char>, Option<usize>)> =
+            automaton.transitions(automaton.start()).collect();
+        assert_eq!(
+            start,
+            [(Some('a'), Some(1)), (Some('b'), None), (None, None)]
+        );
+        assert_eq!(automaton.accepts(2), Some(0));
+    }
+}
@@ -0,0 +1,346 @@
+// A BK-tree indexes keys under a metric so that lookups only compare against a fraction of
+// them. Each child hangs off its parent by its distance to the parent, and by the triangle
+// inequality a key within k of the query can only sit under a child whose edge is within
+// k of the query's distance to the parent, so every other branch is skipped. Since only
+// distances up to the largest edge below a node (plus k) can matter, nodes are compared
+// with the bounded distance, which gives up early on keys that are far away.
+
+use std::borrow::Borrow;
+use std::collections::BinaryHeap;
+
+use crate::{distance_with, distance_within_with, Unit};
+
+/// Metric is a distance between values of type T: it's zero only between equal values,
+/// symmetric, and satisfies the triangle inequality. A BkTree finds wrong results under
+/// anything that isn't a metric, such as the optimal string alignment distance
+pub trait Metric<T: ?Sized> {
+    fn distance(&self, a: &T, b: &T) -> usize;
+
+    /// Distance_within returns the distance between a and b if it is at most max, or None
+    /// otherwise. Metrics with a faster bounded form should override it
+    fn distance_within(&self, a: &T, b: &T, max: usize) -> Option<usize> {
+        Some(self.distance(a, b)).filter(|&d| d <= max)
+    }
+}
+
+/// Unit is the Levenshtein distance counting symbols in that unit
+impl Metric<str> for Unit {
+    fn distance(&self, a: &str, b: &str) -> usize {
+        distance_with(a, b, *self)
+    }
+
+    fn distance_within(&self, a: &str, b: &str, max: usize) -> Option<usize> {
+        distance_within_with(a, b, max, *self)
+    }
+}
+
+impl Metric<String> for Unit {
+    fn distance(&self, a: &String, b: &String) -> usize {
+        distance_with(a, b, *self)
+    }
+
+    fn distance_within(&self, a: &String, b: &String, max: usize) -> Option<usize> {
+        distance_within_with(a, b, max, *self)
+    }
+}
+
+/// BkTree is an index of keys for finding those close to a query under a metric, which is
+/// the Levenshtein distance between chars unless another is given
+///
+/// ```
+/// use lev_rs::BkTree;
+///
+/// let tree: BkTree<String> = ["book", "books", "cake", "boo", "cape", "cart"]
+///     .iter()
+///     .map(|s| s.to_string())
+///     .collect();
+///
+/// let found: Vec<(&str, usize)> = tree
+///     .find_within("bo", 1)
+///     .into_iter()
+///     .map(|(k, d)| (k.as_str(), d))
+///     .collect();
+/// assert_eq!(found, [("boo", 1)]);
+///
+/// let nearest = tree.nearest("cale", 2);
+/// assert_eq!(nearest.len(), 2);
+/// assert_eq!(nearest[0].1, 1);
+/// ```
+pub struct BkTree<K, M = Unit> {
+    metric: M,
+    nodes: Vec<Node<K>>,
+}
+
+// Node is a key in the tree. children are (edge, index) pairs, where edge is the child's
+// distance to this key, and max_edge is the largest of them
+struct Node<K> {
+    key: K,
+    children: Vec<(usize, usize)>,
+    max_edge: usize,
+}
+
+impl<K, M: Metric<K> + Default> BkTree<K, M> {
+    pub fn new() -> Self {
+        BkTree::with_metric(M::default())
+    }
+}
+
+impl<K, M: Metric<K> + Default> Default for BkTree<K, M> {
+    fn default() -> Self {
+        BkTree::new()
+    }
+}
+
+impl<K, M: Metric<K>> BkTree<K, M> {
+    pub fn with_metric(metric: M) -> Self {
+        BkTree {
+            metric,
+            nodes: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Insert adds key to the tree, returning false if an equal key was already there
+    pub fn insert(&mut self, key: K) -> bool {
+        if self.nodes.is_empty() {
+            self.push(key);
+            return true;
+        }
+
+        let mut i = 0;
+        loop {
+            let d = self.metric.distance(&self.nodes[i].key, &key);
+            if d == 0 {
+                return false;
+            }
+
+            match self.nodes[i].children.iter().find(|&&(edge, _)| edge == d) {
+                Some(&(_, child)) => i = child,
+                None => {
+                    let child = self.push(key);
+                    let node = &mut self.nodes[i];
+                    node.children.push((d, child));
+                    node.max_edge = node.max_edge.max(d);
+                    return true;
+                }
+            }
+        }
+    }
+
+    /// Find_within returns every key within k of query along with its distance, closest
+    /// first
+    pub fn find_within<Q>(&self, query: &Q, k: usize) -> Vec<(&K, usize)>
+    where
+        Q: ?Sized,
+        K: Borrow<Q>,
+        M: Metric<Q>,
+    {
+        let mut found = Vec::new();
+        if self.nodes.is_empty() {
+            return found;
+        }
+
+        let mut stack = vec![0];
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+
+            // Past k + max_edge neither this key nor any below it can be within k
+            let Some(d) = self
+                .metric
+                .distance_within(query, node.key.borrow(), k + node.max_edge)
+            else {
+                continue;
+            };
+            if d <= k {
+                found.push((&node.key, d));
+            }
+
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|&&(edge, _)| edge.abs_diff(d) <= k)
+                    .map(|&(_, child)| child),
+            );
+        }
+
+        found.sort_by_key(|&(_, d)| d);
+        found
+    }
+
+    /// Nearest returns the n keys closest to query along with their distances, closest
+    /// first. Keys at the same distance as the furthest one returned may be left out
+    pub fn nearest<Q>(&self, query: &Q, n: usize) -> Vec<(&K, usize)>
+    where
+        Q: ?Sized,
+        K: Borrow<Q>,
+        M: Metric<Q>,
+    {
+        if self.nodes.is_empty() || n == 0 {
+            return Vec::new();
+        }
+
+        // best holds the closest keys found so far, furthest on top, and once it has n of
+        // them the search radius shrinks to the furthest
+        let mut best: BinaryHeap<(usize, usize)> = BinaryHeap::with_capacity(n + 1);
+        let radius = |best: &BinaryHeap<(usize, usize)>| match best.peek() {
+            Some(&(d, _)) if best.len() == n => d,
+            _ => usize::MAX,
+        };
+
+        let mut stack = vec![0];
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            let r = radius(&best);
+            let Some(d) = self.metric.distance_within(
+                query,
+                node.key.borrow(),
+                r.saturating_add(node.max_edge),
+            ) else {
+                continue;
+            };
+
+            if d < r {
+                best.push((d, i));
+                if best.len() > n {
+                    best.pop();
+                }
+            }
+
+            let r = radius(&best);
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|&&(edge, _)| edge.abs_diff(d) <= r)
+                    .map(|&(_, child)| child),
+            );
+        }
+
+        let mut found: Vec<(&K, usize)> = best
+            .into_iter()
+            .map(|(d, i)| (&self.nodes[i].key, d))
+            .collect();
+        found.sort_by_key(|&(_, d)| d);
+        found
+    }
+
+    fn push(&mut self, key: K) -> usize {
+        self.nodes.push(Node {
+            key,
+            children: Vec::new(),
+            max_edge: 0,
+        });
+        self.nodes.len() - 1
+    }
+}
+
+impl<K, M: Metric<K>> Extend<K> for BkTree<K, M> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, keys: I) {
+        for key in keys {
+            self.insert(key);
+        }
+    }
+}
+
+impl<K, M: Metric<K> + Default> FromIterator<K> for BkTree<K, M> {
+    fn from_iter<I: IntoIterator<Item = K>>(keys: I) -> Self {
+        let mut tree = BkTree::new();
+        tree.extend(keys);
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: [&str; 16] = [
+        "kitten", "sitting", "mitten", "bitten", "kitchen", "smitten", "written", "knitting",
+        "fast", "past", "pasta", "last", "cat", "cart", "chart", "charts",
+    ];
+
+    #[test]
+    fn it_finds_words_within() {
+        let tree: BkTree<String> = WORDS.iter().map(|w| w.to_string()).collect();
+        assert_eq!(tree.len(), WORDS.len());
+
+        for query in ["kitten", "past", "car", "", "knitten", "xyz"] {
+            for k in 0..=4 {
+                let mut want: Vec<(&str, usize)> = WORDS
+                    .iter()
+                    .map(|w| (*w, crate::distance(query, w)))
+                    .filter(|&(_, d)| d <= k)
+                    .collect();
+                want.sort();
+
+                let mut got: Vec<(&str, usize)> = tree
+                    .find_within(query, k)
+                    .into_iter()
+                    .map(|(w, d)| (w.as_str(), d))
+                    .collect();
+                got.sort();
+                assert_eq!(
+                    got, want,
+                    "find_within({}, {}) - got {:?}, want {:?}",
+                    query, k, got, want
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_finds_nearest() {
+        let tree: BkTree<String> = WORDS.iter().map(|w| w.to_string()).collect();
+
+        for query in ["kitten", "past", "car", "", "knitten", "xyz"] {
+            let mut all: Vec<usize> = WORDS.iter().map(|w| crate::distance(query, w)).collect();
+            all.sort();
+
+            for n in [0, 1, 3, 5, 16, 20] {
+                let got: Vec<usize> = tree.nearest(query, n).iter().map(|&(_, d)| d).collect();
+                let want = &all[..n.min(all.len())];
+                assert_eq!(
+                    got, want,
+                    "nearest({}, {}) - got {:?}, want {:?}",
+                    query, n, got, want
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_takes_any_metric() {
+        // Absolute difference between numbers
+        #[derive(Default)]
+        struct Gap;
+        impl Metric<i32> for Gap {
+            fn distance(&self, a: &i32, b: &i32) -> usize {
+                a.abs_diff(*b) as usize
+            }
+        }
+
+        let mut tree: BkTree<i32, Gap> = BkTree::new();
+        tree.extend([10, 20, 30, 12, 19, 40]);
+        assert!(!tree.insert(12));
+        assert_eq!(tree.len(), 6);
+
+        let got: Vec<(i32, usize)> = tree
+            .find_within(&18, 2)
+            .into_iter()
+            .map(|(k, d)| (*k, d))
+            .collect();
+        assert_eq!(got, [(19, 1), (20, 2)]);
+
+        let got: Vec<i32> = tree.nearest(&33, 2).into_iter().map(|(k, _)| *k).collect();
+        assert_eq!(got, [30, 40]);
+
+        let bytes = BkTree::<String, Unit>::with_metric(Unit::Byte);
+        assert!(bytes.find_within("naïve", 1).is_empty());
+    }
+}
@@ -0,0 +1,182 @@
+// Bounded distance answers "are a and b within max edits, and if so how many?" without
+// filling the whole DP grid. After cheap checks on the lengths and stripping any common
+// prefix and suffix, it runs the single_row_distance sweep restricted to Ukkonen's
+// diagonal band: a cell more than max columns away from the diagonal can't lie on a path
+// costing max or less, so only 2 * max + 1 cells per row are computed, and the sweep
+// stops as soon as every cell in a row is over the bound.
+
+use std::cmp;
+
+use crate::{min3, myers, Unit};
+
+/// Distance_within returns the Levenshtein distance between a and b if it is at most
+/// max, or None otherwise, counting each char as one symbol. It runs in O(max * n) time
+/// rather than the O(n * m) of distance, and like distance uses the bit-parallel
+/// algorithm, restricted to the same band
+///
+/// ```
+/// assert_eq!(lev_rs::distance_within("kitten", "sitting", 3), Some(3));
+/// assert_eq!(lev_rs::distance_within("kitten", "sitting", 2), None);
+/// ```
+pub fn distance_within(a: &str, b: &str, max: usize) -> Option<usize> {
+    distance_within_with(a, b, max, Unit::Char)
+}
+
+/// Distance_within_with returns the Levenshtein distance between a and b if it is at most
+/// max, or None otherwise, counting symbols in the given unit
+pub fn distance_within_with(a: &str, b: &str, max: usize, unit: Unit) -> Option<usize> {
+    with_units!(unit, a, b, |a, b| myers::fast_distance_within(a, b, max))
+}
+
+/// Distance_within_slices returns the Levenshtein distance between sequences a and b if
+/// it is at most max, or None otherwise
+pub fn distance_within_slices<T: PartialEq>(a: &[T], b: &[T], max: usize) -> Option<usize> {
+    // Every extra symbol in the longer input needs at least one insertion
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    let (a, b) = strip_common_affixes(a, b);
+
+    if a.is_empty() || b.is_empty() {
+        // The length check above already bounds this
+        return Some(a.len() + b.len());
+    }
+
+    if max == 0 {
+        // Whatever was left over after stripping is a difference
+        return None;
+    }
+
+    // Run the band along the shorter input
+    let (a, b) = if a.len() > b.len() { (b, a) } else { (a, b) };
+    banded_distance(a, b, max)
+}
+
+// strip_common_affixes removes the prefix and suffix a and b share, which never affect
+// the distance between them
+pub(crate) fn strip_common_affixes<'t, T: PartialEq>(a: &'t [T], b: &'t [T]) -> (&'t [T], &'t [T]) {
+    let prefix = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+
+    let suffix = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    (&a[..a.len() - suffix], &b[..b.len() - suffix])
+}
+
+// banded_distance is single_row_distance restricted to the cells within max of the
+// diagonal. Anything over max is stored as max + 1, so the row never holds values that
+// could overflow or need to be told apart
+fn banded_distance<T: PartialEq>(a: &[T], b: &[T], max: usize) -> Option<usize> {
+    let over = max + 1;
+    let mut row: Vec<usize> = (0..=a.len()).map(|x| cmp::min(x, over)).collect();
+
+    for y in 1..=b.len() {
+        let low = if y > max { y - max } else { 1 };
+        let high = cmp::min(a.len(), y + max);
+
+        // The cell just left of the band is the first column if the band touches it, and
+        // out of bounds otherwise
+        let mut last = row[low - 1];
+        row[low - 1] = if low == 1 { cmp::min(y, over) } else { over };
+        let mut row_min = row[low - 1];
+
+        for x in low..=high {
+            let above = row[x];
+            let cell = if a[x - 1] == b[y - 1] {
+                last
+            } else {
+                1 + min3(last, row[x - 1], above)
+            };
+
+            last = above;
+            row[x] = cmp::min(cell, over);
+            row_min = cmp::min(row_min, row[x]);
+        }
+
+        if row_min > max {
+            return None;
+        }
+
+        // The next row's band reaches one column further right, into a cell this row
+        // never wrote
+        if high < a.len() {
+            row[high + 1] = over;
+        }
+    }
+
+    Some(row[a.len()]).filter(|&d| d <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_agrees_with_distance() {
+        let words = [
+            "",
+            "a",
+            "ab",
+            "ba",
+            "fast",
+            "past",
+            "foo",
+            "bar",
+            "kitten",
+            "sitting",
+            "ababab",
+            "bababa",
+            "aaaaaa",
+            "intention",
+            "execution",
+            "the quick brown fox",
+            "the quick brown dog",
+            "naïve",
+        ];
+
+        for a in words.iter() {
+            for b in words.iter() {
+                let d = crate::distance(a, b);
+                for max in 0..=d + 2 {
+                    let want = if d <= max { Some(d) } else { None };
+                    let got = distance_within(a, b, max);
+                    assert_eq!(
+                        got, want,
+                        "distance_within({}, {}, {}) - got {:?}, want {:?}",
+                        a, b, max, got, want
+                    );
+
+                    let got = distance_within_slices(a.as_bytes(), b.as_bytes(), max);
+                    let d = crate::distance_slices(a.as_bytes(), b.as_bytes());
+                    let want = if d <= max { Some(d) } else { None };
+                    assert_eq!(
+                        got, want,
+                        "distance_within_slices({}, {}, {}) - got {:?}, want {:?}",
+                        a, b, max, got, want
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_strips_affixes() {
+        let test_cases = [
+            ("abcxdef", "abcydef", "x", "y"),
+            ("abc", "abc", "", ""),
+            ("abc", "abcd", "", "d"),
+            ("xabc", "abc", "x", ""),
+            ("aaa", "aa", "a", ""),
+        ];
+
+        for tc in test_cases.iter() {
+            let (a, b) = strip_common_affixes(tc.0.as_bytes(), tc.1.as_bytes());
+            assert_eq!((a, b), (tc.2.as_bytes(), tc.3.as_bytes()));
+        }
+    }
+}
@@ -0,0 +1,278 @@
+// Weighted edit distance, where a CostModel decides what each insertion, deletion and
+// substitution costs. This is kept apart from the unit-cost implementations in lib.rs so
+// distance never pays for the extra indirection.
+
+use std::ops::Add;
+
+/// Cost is a number edit costs can be counted in. It is implemented for the primitive
+/// integers and floats; costs are expected never to be negative
+pub trait Cost: Copy + PartialOrd + Add<Output = Self> {
+    const ZERO: Self;
+    const ONE: Self;
+}
+
+macro_rules! impl_cost {
+    ($($t:ty => $zero:expr, $one:expr);* $(;)?) => {
+        $(
+            impl Cost for $t {
+                const ZERO: Self = $zero;
+                const ONE: Self = $one;
+            }
+        )*
+    };
+}
+
+impl_cost! {
+    u8 => 0, 1;
+    u16 => 0, 1;
+    u32 => 0, 1;
+    u64 => 0, 1;
+    usize => 0, 1;
+    i32 => 0, 1;
+    i64 => 0, 1;
+    f32 => 0.0, 1.0;
+    f64 => 0.0, 1.0;
+}
+
+/// CostModel assigns a cost to every edit of symbols of type T. Substituting a symbol
+/// for an equal one is always free, so substitute_cost is only asked about symbols that
+/// differ
+///
+/// ```
+/// use lev_rs::{weighted_distance, CostModel};
+///
+/// // Confusing one vowel for another is half as bad as any other mistake
+/// struct Vowels;
+///
+/// impl CostModel<char> for Vowels {
+///     type Cost = f64;
+///
+///     fn insert_cost(&self, _: &char) -> f64 {
+///         1.0
+///     }
+///
+///     fn delete_cost(&self, _: &char) -> f64 {
+///         1.0
+///     }
+///
+///     fn substitute_cost(&self, a: &char, b: &char) -> f64 {
+///         if "aeiou".contains(*a) && "aeiou".contains(*b) {
+///             0.5
+///         } else {
+///             1.0
+///         }
+///     }
+/// }
+///
+/// assert_eq!(weighted_distance("sit", "set", &Vowels), 0.5);
+/// assert_eq!(weighted_distance("sit", "sip", &Vowels), 1.0);
+/// ```
+pub trait CostModel<T: ?Sized> {
+    type Cost: Cost;
+
+    fn insert_cost(&self, symbol: &T) -> Self::Cost;
+    fn delete_cost(&self, symbol: &T) -> Self::Cost;
+    fn substitute_cost(&self, a: &T, b: &T) -> Self::Cost;
+}
+
+/// UnitCost charges 1 for every edit, giving the same result as distance
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct UnitCost;
+
+impl<T: ?Sized> CostModel<T> for UnitCost {
+    type Cost = usize;
+
+    fn insert_cost(&self, _: &T) -> usize {
+        1
+    }
+
+    fn delete_cost(&self, _: &T) -> usize {
+        1
+    }
+
+    fn substitute_cost(&self, _: &T, _: &T) -> usize {
+        1
+    }
+}
+
+/// Weights charges a fixed cost for each kind of edit, regardless of the symbols involved
+///
+/// ```
+/// use lev_rs::{weighted_distance, Weights};
+///
+/// let costly_deletes = Weights { insert: 1, delete: 3, substitute: 1 };
+/// assert_eq!(weighted_distance("cats", "cat", &costly_deletes), 3);
+/// assert_eq!(weighted_distance("cat", "cats", &costly_deletes), 1);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Weights<C> {
+    pub insert: C,
+    pub delete: C,
+    pub substitute: C,
+}
+
+impl<T: ?Sized, C: Cost> CostModel<T> for Weights<C> {
+    type Cost = C;
+
+    fn insert_cost(&self, _: &T) -> C {
+        self.insert
+    }
+
+    fn delete_cost(&self, _: &T) -> C {
+        self.delete
+    }
+
+    fn substitute_cost(&self, _: &T, _: &T) -> C {
+        self.substitute
+    }
+}
+
+/// Weighted_distance returns the cheapest total cost of edits turning a into b, counting
+/// each char as one symbol
+pub fn weighted_distance<C: CostModel<char>>(a: &str, b: &str, costs: &C) -> C::Cost {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    weighted_distance_slices(&a, &b, costs)
+}
+
+/// Weighted_distance_slices returns the cheapest total cost of edits turning sequence a
+/// into sequence b
+pub fn weighted_distance_slices<T, C>(a: &[T], b: &[T], costs: &C) -> C::Cost
+where
+    T: PartialEq,
+    C: CostModel<T>,
+{
+    // The same sweep as single_row_distance, except the first row and column are running
+    // totals of deletions and insertions rather than simple counts
+    let mut row = Vec::with_capacity(a.len() + 1);
+    row.push(C::Cost::ZERO);
+    for (x, ax) in a.iter().enumerate() {
+        row.push(row[x] + costs.delete_cost(ax));
+    }
+
+    let mut last;
+
+    for by in b.iter() {
+        (last, row[0]) = (row[0], row[0] + costs.insert_cost(by));
+        for (x, ax) in a.iter().enumerate() {
+            let substitute = if ax == by {
+                last
+            } else {
+                last + costs.substitute_cost(ax, by)
+            };
+            let delete = row[x] + costs.delete_cost(ax);
+            let insert = row[x + 1] + costs.insert_cost(by);
+
+            last = row[x + 1];
+            row[x + 1] = min_cost(min_cost(substitute, delete), insert);
+        }
+    }
+
+    row[a.len()]
+}
+
+// min_cost is cmp::min for costs that are only PartialOrd, such as floats
+fn min_cost<C: Cost>(a: C, b: C) -> C {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_matches_distance_with_unit_costs() {
+        let test_cases = [
+            ("", ""),
+            ("", "abc"),
+            ("abc", ""),
+            ("fast", "past"),
+            ("foo", "bar"),
+            ("ababab", "bababa"),
+            ("aabaa", "aaaa"),
+            ("kitten", "sitting"),
+            ("naïve", "naive"),
+        ];
+
+        for tc in test_cases.iter() {
+            let want = crate::distance(tc.0, tc.1);
+            let got = weighted_distance(tc.0, tc.1, &UnitCost);
+            assert_eq!(
+                got, want,
+                "weighted_distance({}, {}) - got {}, want {}",
+                tc.0, tc.1, got, want
+            );
+
+            let weights = Weights {
+                insert: 1.0,
+                delete: 1.0,
+                substitute: 1.0,
+            };
+            let got = weighted_distance(tc.0, tc.1, &weights);
+            assert_eq!(got, want as f64);
+        }
+    }
+
+    #[test]
+    fn it_weighs_edits() {
+        // a, b, (insert, delete, substitute), want
+        let test_cases = [
+            // Substitutions cost more than an insert plus a delete, so they are avoided
+            ("fast", "past", (1, 1, 5), 2),
+            ("fast", "past", (1, 1, 1), 1),
+            ("abc", "", (1, 4, 1), 12),
+            ("", "abc", (2, 4, 1), 6),
+            ("abcd", "ab", (1, 3, 1), 6),
+            ("ab", "abcd", (1, 3, 1), 2),
+        ];
+
+        for tc in test_cases.iter() {
+            let (insert, delete, substitute) = tc.2;
+            let weights: Weights<u32> = Weights {
+                insert,
+                delete,
+                substitute,
+            };
+            let got = weighted_distance(tc.0, tc.1, &weights);
+            assert_eq!(
+                got, tc.3,
+                "weighted_distance({}, {}, {:?}) - got {}, want {}",
+                tc.0, tc.1, weights, got, tc.3
+            );
+        }
+    }
+
+    #[test]
+    fn it_weighs_symbols() {
+        // Deleting spaces is free, anything else costs its byte value
+        struct Spaces;
+
+        impl CostModel<u8> for Spaces {
+            type Cost = u32;
+
+            fn insert_cost(&self, c: &u8) -> u32 {
+                *c as u32
+            }
+
+            fn delete_cost(&self, c: &u8) -> u32 {
+                if *c == b' ' {
+                    0
+                } else {
+                    *c as u32
+                }
+            }
+
+            fn substitute_cost(&self, _: &u8, _: &u8) -> u32 {
+                1000
+            }
+        }
+
+        assert_eq!(weighted_distance_slices(b"a b c", b"abc", &Spaces), 0);
+        assert_eq!(weighted_distance_slices(b"abc", b"a b c", &Spaces), 64);
+        assert_eq!(weighted_distance_slices(b"a", b"b", &Spaces), 195);
+    }
+}
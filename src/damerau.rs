@@ -0,0 +1,448 @@
+// Damerau-Levenshtein distances additionally allow swapping two adjacent symbols for a
+// cost of 1, so the common "teh" -> "the" typo is one edit rather than two.
+//
+// There are two flavours. Optimal string alignment (OSA) only allows a transposition
+// when nothing else touches the swapped pair, which keeps the DP to a single extra
+// lookback but breaks the triangle inequality: osa("ca", "abc") is 3. The unrestricted
+// distance lets symbols be inserted between a swapped pair, giving "ca" -> "ac" -> "abc"
+// for a distance of 2, at the cost of remembering where each symbol was last seen.
+//
+// An unrestricted edit script can swap symbols that aren't next to each other, so its
+// Transpose is followed by the deletions and insertions between the swapped pair. To
+// find them the traceback looks back for the same earlier occurrences the DP used.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::edit::traceback;
+use crate::{min3, Edit, EditOp, EditScript, Matrix, Unit};
+
+/// Osa_distance returns the optimal string alignment distance between a and b, counting
+/// each char as one symbol
+///
+/// ```
+/// assert_eq!(lev_rs::osa_distance("teh", "the"), 1);
+/// assert_eq!(lev_rs::distance("teh", "the"), 2);
+/// ```
+pub fn osa_distance(a: &str, b: &str) -> usize {
+    osa_distance_with(a, b, Unit::Char)
+}
+
+/// Osa_distance_with returns the optimal string alignment distance between a and b,
+/// counting symbols in the given unit
+pub fn osa_distance_with(a: &str, b: &str, unit: Unit) -> usize {
+    with_units!(unit, a, b, |a, b| osa_distance_slices(a, b))
+}
+
+/// Osa_distance_slices returns the optimal string alignment distance between sequences
+/// a and b
+pub fn osa_distance_slices<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    if a.is_empty() {
+        return b.len();
+    }
+
+    if b.is_empty() {
+        return a.len();
+    }
+
+    osa_matrix(a, b).last()
+}
+
+/// Osa_edit_script returns the edits that turn a into b, counting each char as one
+/// symbol and allowing adjacent transpositions
+///
+/// ```
+/// use lev_rs::{osa_edit_script, EditOp};
+///
+/// let ops: Vec<EditOp> = osa_edit_script("teh", "the").iter().map(|e| e.op).collect();
+/// assert_eq!(ops, [EditOp::Match, EditOp::Transpose]);
+/// ```
+pub fn osa_edit_script(a: &str, b: &str) -> EditScript {
+    osa_edit_script_with(a, b, Unit::Char)
+}
+
+/// Osa_edit_script_with returns the edits that turn a into b, counting symbols in the
+/// given unit and allowing adjacent transpositions
+pub fn osa_edit_script_with(a: &str, b: &str, unit: Unit) -> EditScript {
+    with_units!(unit, a, b, |a, b| osa_edit_script_slices(a, b))
+}
+
+/// Osa_edit_script_slices returns the edits that turn sequence a into sequence b,
+/// allowing adjacent transpositions
+pub fn osa_edit_script_slices<T: PartialEq>(a: &[T], b: &[T]) -> EditScript {
+    let matrix = osa_matrix(a, b);
+
+    traceback(
+        a.len(),
+        b.len(),
+        |x, y| x == 0 && y == 0,
+        |x, y| {
+            let cell = matrix.get(x, y);
+            if x > 0 && y > 0 {
+                if a[x - 1] == b[y - 1] && matrix.get(x - 1, y - 1) == cell {
+                    return EditOp::Match;
+                }
+                if is_transposition(a, b, x, y) && matrix.get(x - 2, y - 2) + 1 == cell {
+                    return EditOp::Transpose;
+                }
+                if matrix.get(x - 1, y - 1) + 1 == cell {
+                    return EditOp::Substitute;
+                }
+            }
+            if x > 0 && matrix.get(x - 1, y) + 1 == cell {
+                EditOp::Delete
+            } else {
+                EditOp::Insert
+            }
+        },
+    )
+}
+
+// osa_matrix builds the same grid as levenshtein_matrix, with each cell also allowed to
+// come from two cells up the diagonal when the last two symbols are swapped
+fn osa_matrix<T: PartialEq>(a: &[T], b: &[T]) -> Matrix {
+    let mut matrix = Matrix::new(a.len() + 1, b.len() + 1, 0);
+
+    for x in 0..=a.len() {
+        matrix.set(x, 0, x);
+    }
+
+    for y in 0..=b.len() {
+        matrix.set(0, y, y);
+    }
+
+    for y in 1..=b.len() {
+        for x in 1..=a.len() {
+            let cost = if a[x - 1] == b[y - 1] { 0 } else { 1 };
+            let mut cell = min3(
+                matrix.get(x - 1, y) + 1,
+                matrix.get(x, y - 1) + 1,
+                matrix.get(x - 1, y - 1) + cost,
+            );
+            if is_transposition(a, b, x, y) {
+                cell = cell.min(matrix.get(x - 2, y - 2) + 1);
+            }
+            matrix.set(x, y, cell);
+        }
+    }
+
+    matrix
+}
+
+// is_transposition reports whether the two symbols of a ending at x are the two symbols
+// of b ending at y, swapped
+fn is_transposition<T: PartialEq>(a: &[T], b: &[T], x: usize, y: usize) -> bool {
+    x > 1 && y > 1 && a[x - 1] == b[y - 2] && a[x - 2] == b[y - 1] && a[x - 1] != a[x - 2]
+}
+
+/// Damerau_distance returns the unrestricted Damerau-Levenshtein distance between a and
+/// b, counting each char as one symbol
+///
+/// ```
+/// assert_eq!(lev_rs::damerau_distance("ca", "abc"), 2);
+/// assert_eq!(lev_rs::osa_distance("ca", "abc"), 3);
+/// ```
+pub fn damerau_distance(a: &str, b: &str) -> usize {
+    damerau_distance_with(a, b, Unit::Char)
+}
+
+/// Damerau_distance_with returns the unrestricted Damerau-Levenshtein distance between a
+/// and b, counting symbols in the given unit
+pub fn damerau_distance_with(a: &str, b: &str, unit: Unit) -> usize {
+    with_units!(unit, a, b, |a, b| damerau_distance_slices(a, b))
+}
+
+/// Damerau_distance_slices returns the unrestricted Damerau-Levenshtein distance between
+/// sequences a and b
+pub fn damerau_distance_slices<T: Eq + Hash>(a: &[T], b: &[T]) -> usize {
+    if a.is_empty() {
+        return b.len();
+    }
+
+    if b.is_empty() {
+        return a.len();
+    }
+
+    damerau_matrix(a, b).last()
+}
+
+/// Damerau_edit_script returns the edits that turn a into b under the unrestricted
+/// Damerau-Levenshtein distance, counting each char as one symbol. A Transpose may be
+/// followed by the edits between its swapped symbols, as Edit describes
+///
+/// ```
+/// use lev_rs::{damerau_edit_script, EditOp};
+///
+/// // Swap "ca" to "ac", then insert "b" between them
+/// let script = damerau_edit_script("ca", "abc");
+/// let ops: Vec<EditOp> = script.iter().map(|e| e.op).collect();
+/// assert_eq!(ops, [EditOp::Transpose, EditOp::Insert]);
+/// assert_eq!(script.distance(), 2);
+///
+/// let chars = |s: &str| s.chars().collect::<Vec<_>>();
+/// assert_eq!(script.alignment(&chars("ca"), &chars("abc")).to_string(), "c-a\nabc");
+/// ```
+pub fn damerau_edit_script(a: &str, b: &str) -> EditScript {
+    damerau_edit_script_with(a, b, Unit::Char)
+}
+
+/// Damerau_edit_script_with returns the edits that turn a into b under the unrestricted
+/// Damerau-Levenshtein distance, counting symbols in the given unit
+pub fn damerau_edit_script_with(a: &str, b: &str, unit: Unit) -> EditScript {
+    with_units!(unit, a, b, |a, b| damerau_edit_script_slices(a, b))
+}
+
+/// Damerau_edit_script_slices returns the edits that turn sequence a into sequence b
+/// under the unrestricted Damerau-Levenshtein distance
+pub fn damerau_edit_script_slices<T: Eq + Hash>(a: &[T], b: &[T]) -> EditScript {
+    let matrix = damerau_matrix(a, b);
+    // distance gives the cell for a[..x] and b[..y], past the extra row and column
+    let distance = |x: usize, y: usize| matrix.get(x + 1, y + 1);
+
+    let (mut x, mut y) = (a.len(), b.len());
+    let mut edits = Vec::with_capacity(x.max(y));
+    let mut push = |op, x, y| edits.push(Edit { op, a: x, b: y });
+
+    while x > 0 || y > 0 {
+        let cell = distance(x, y);
+        if x > 0 && y > 0 {
+            if a[x - 1] == b[y - 1] && distance(x - 1, y - 1) == cell {
+                x -= 1;
+                y -= 1;
+                push(EditOp::Match, x, y);
+                continue;
+            }
+            if is_transposition(a, b, x, y) && distance(x - 2, y - 2) + 1 == cell {
+                x -= 2;
+                y -= 2;
+                push(EditOp::Transpose, x, y);
+                continue;
+            }
+            if distance(x - 1, y - 1) + 1 == cell {
+                x -= 1;
+                y -= 1;
+                push(EditOp::Substitute, x, y);
+                continue;
+            }
+        }
+        if x > 0 && distance(x - 1, y) + 1 == cell {
+            x -= 1;
+            push(EditOp::Delete, x, y);
+            continue;
+        }
+        if y > 0 && distance(x, y - 1) + 1 == cell {
+            y -= 1;
+            push(EditOp::Insert, x, y);
+            continue;
+        }
+
+        // Only a transposition across other edits is left. a[col - 1] is the last symbol
+        // before a[x - 1] equal to b[y - 1], and b[row - 1] the last before b[y - 1] equal
+        // to a[x - 1], just as the DP found them
+        let col = (1..x).rev().find(|&c| a[c - 1] == b[y - 1]);
+        let row = (1..y).rev().find(|&r| b[r - 1] == a[x - 1]);
+        let (col, row) = col
+            .zip(row)
+            .expect("every cell comes from one of its neighbours");
+        debug_assert_eq!(
+            distance(col - 1, row - 1) + (x - col - 1) + 1 + (y - row - 1),
+            cell
+        );
+
+        // Edits are pushed in reverse, so the inserts and deletes go before the Transpose
+        for y in (row..y - 1).rev() {
+            push(EditOp::Insert, x - 1, y);
+        }
+        for x in (col..x - 1).rev() {
+            push(EditOp::Delete, x, row);
+        }
+        push(EditOp::Transpose, col - 1, row - 1);
+        (x, y) = (col - 1, row - 1);
+    }
+
+    edits.reverse();
+    EditScript::new(edits)
+}
+
+// damerau_matrix builds the grid for the unrestricted distance. It has an extra leading
+// row and column filled with a value larger than any real distance, so transpositions
+// reaching past the start of either input are never chosen. Cell (x + 1, y + 1) holds
+// the distance between a[..x] and b[..y]
+fn damerau_matrix<T: Eq + Hash>(a: &[T], b: &[T]) -> Matrix {
+    let infinity = a.len() + b.len();
+    let mut matrix = Matrix::new(a.len() + 2, b.len() + 2, infinity);
+    for x in 0..=a.len() {
+        matrix.set(x + 1, 1, x);
+    }
+    for y in 0..=b.len() {
+        matrix.set(1, y + 1, y);
+    }
+
+    // last_row maps each symbol to the last row of b it appeared in so far
+    let mut last_row: HashMap<&T, usize> = HashMap::new();
+
+    for y in 1..=b.len() {
+        // The last column in this row where a matched b[y - 1]
+        let mut last_col = 0;
+
+        for x in 1..=a.len() {
+            let row = last_row.get(&a[x - 1]).copied().unwrap_or(0);
+            let col = last_col;
+
+            let cost = if a[x - 1] == b[y - 1] {
+                last_col = x;
+                0
+            } else {
+                1
+            };
+
+            // Transpose a[col - 1] with a[x - 1], deleting everything in a between them
+            // and inserting everything in b between b[row - 1] and b[y - 1]
+            let transposition = matrix.get(col, row) + (x - col - 1) + 1 + (y - row - 1);
+
+            let cell = min3(
+                matrix.get(x, y) + cost,
+                matrix.get(x + 1, y) + 1,
+                matrix.get(x, y + 1) + 1,
+            )
+            .min(transposition);
+            matrix.set(x + 1, y + 1, cell);
+        }
+
+        last_row.insert(&b[y - 1], y);
+    }
+
+    matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_counts_transpositions() {
+        // a, b, levenshtein, osa, damerau
+        let test_cases = [
+            ("", "", 0, 0, 0),
+            ("", "abc", 3, 3, 3),
+            ("abc", "", 3, 3, 3),
+            ("teh", "the", 2, 1, 1),
+            ("ab", "ba", 2, 1, 1),
+            ("abcdef", "badcfe", 4, 3, 3),
+            ("ca", "abc", 3, 3, 2),
+            ("abc", "ca", 3, 3, 2),
+            ("a cat", "an act", 3, 2, 2),
+            ("fast", "past", 1, 1, 1),
+            ("aabaa", "aaaa", 1, 1, 1),
+            ("kitten", "sitting", 3, 3, 3),
+            ("recieve", "receive", 2, 1, 1),
+            ("héllo", "hlélo", 2, 1, 1),
+        ];
+
+        for tc in test_cases.iter() {
+            let lev = crate::distance(tc.0, tc.1);
+            assert_eq!(
+                lev, tc.2,
+                "distance({}, {}) - got {}, want {}",
+                tc.0, tc.1, lev, tc.2
+            );
+
+            let osa = osa_distance(tc.0, tc.1);
+            assert_eq!(
+                osa, tc.3,
+                "osa_distance({}, {}) - got {}, want {}",
+                tc.0, tc.1, osa, tc.3
+            );
+
+            let damerau = damerau_distance(tc.0, tc.1);
+            assert_eq!(
+                damerau, tc.4,
+                "damerau_distance({}, {}) - got {}, want {}",
+                tc.0, tc.1, damerau, tc.4
+            );
+
+            let script = osa_edit_script(tc.0, tc.1);
+            assert_eq!(
+                script.distance(),
+                tc.3,
+                "osa_edit_script({}, {}) - got {:?}",
+                tc.0,
+                tc.1,
+                script
+            );
+
+            let script = damerau_edit_script(tc.0, tc.1);
+            assert_eq!(
+                script.distance(),
+                tc.4,
+                "damerau_edit_script({}, {}) - got {:?}",
+                tc.0,
+                tc.1,
+                script
+            );
+        }
+    }
+
+    #[test]
+    fn it_traces_transpositions() {
+        let a: Vec<char> = "recieve".chars().collect();
+        let b: Vec<char> = "receive".chars().collect();
+        let script = osa_edit_script_slices(&a, &b);
+
+        let transposes: Vec<_> = script
+            .iter()
+            .filter(|e| e.op == EditOp::Transpose)
+            .collect();
+        assert_eq!(transposes.len(), 1);
+        assert_eq!((transposes[0].a, transposes[0].b), (3, 3));
+
+        let alignment = script.alignment(&a, &b);
+        assert_eq!(
+            (alignment.a.as_str(), alignment.b.as_str()),
+            ("recieve", "receive")
+        );
+    }
+
+    #[test]
+    fn it_traces_unrestricted_transpositions() {
+        use EditOp::*;
+
+        // a, b, ops, alignment
+        let test_cases: [(&str, &str, &[EditOp], &str); 5] = [
+            ("ca", "abc", &[Transpose, Insert], "c-a\nabc"),
+            ("abc", "ca", &[Transpose, Delete], "abc\nc-a"),
+            ("teh", "the", &[Match, Transpose], "teh\nthe"),
+            (
+                "xcay",
+                "xabcy",
+                &[Match, Transpose, Insert, Match],
+                "xc-ay\nxabcy",
+            ),
+            ("cxya", "ac", &[Transpose, Delete, Delete], "cxya\na--c"),
+        ];
+
+        for tc in test_cases.iter() {
+            let a: Vec<char> = tc.0.chars().collect();
+            let b: Vec<char> = tc.1.chars().collect();
+            let script = damerau_edit_script_slices(&a, &b);
+
+            let ops: Vec<EditOp> = script.iter().map(|e| e.op).collect();
+            assert_eq!(
+                ops, tc.2,
+                "damerau_edit_script({}, {}) - got {:?}, want {:?}",
+                tc.0, tc.1, ops, tc.2
+            );
+            assert_eq!(script.distance(), damerau_distance(tc.0, tc.1));
+
+            let alignment = script.alignment(&a, &b).to_string();
+            assert_eq!(
+                alignment, tc.3,
+                "damerau_edit_script({}, {}).alignment - got {:?}, want {:?}",
+                tc.0, tc.1, alignment, tc.3
+            );
+        }
+
+        assert_eq!(damerau_edit_script("xcay", "xabcy").cigar(), "2M1I2M");
+        assert_eq!(damerau_edit_script("cxya", "ac").cigar(), "1M2D1M");
+    }
+}
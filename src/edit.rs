@@ -0,0 +1,398 @@
+use std::fmt;
+
+use crate::{levenshtein_matrix, Unit};
+
+/// EditOp is one step in turning sequence a into sequence b
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EditOp {
+    /// The symbols in a and b are equal
+    Match,
+    /// The symbol in a is replaced by the symbol in b
+    Substitute,
+    /// The symbol from b is inserted into a
+    Insert,
+    /// The symbol in a is removed
+    Delete,
+    /// Two adjacent symbols in a are swapped to give the next two symbols in b
+    Transpose,
+}
+
+/// Edit is a single EditOp along with where it applies. `a` and `b` are unit offsets into
+/// each input; for an Insert `a` is the position the symbol is inserted before, for a
+/// Delete `b` is the position in b the deleted symbol would have occupied, and for a
+/// Transpose they are the first of the two swapped symbols on each side.
+///
+/// Under the unrestricted Damerau-Levenshtein distance the swapped symbols needn't be
+/// next to each other. A Transpose is then followed by a Delete for each symbol of a
+/// between the pair, at b + 1, and an Insert for each symbol of b between them, before
+/// the second symbol of a. The second swapped symbols come straight after all of those
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Edit {
+    pub op: EditOp,
+    pub a: usize,
+    pub b: usize,
+}
+
+/// EditScript is the ordered list of edits that turns a into b, including matches
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EditScript {
+    edits: Vec<Edit>,
+}
+
+impl EditScript {
+    pub(crate) fn new(edits: Vec<Edit>) -> Self {
+        EditScript { edits }
+    }
+
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Edit> {
+        self.edits.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Distance returns the number of edits other than matches
+    pub fn distance(&self) -> usize {
+        self.edits.iter().filter(|e| e.op != EditOp::Match).count()
+    }
+
+    /// Alignment lines a and b up against each other according to this script. a and b
+    /// must be the same sequences the script was computed from
+    ///
+    /// ```
+    /// let script = lev_rs::edit_script("fast", "pat");
+    /// let chars = |s: &str| s.chars().collect::<Vec<_>>();
+    /// let alignment = script.alignment(&chars("fast"), &chars("pat"));
+    /// assert_eq!(alignment.a, "fast");
+    /// assert_eq!(alignment.b, "pa-t");
+    /// ```
+    pub fn alignment<T: fmt::Display>(&self, a: &[T], b: &[T]) -> Alignment {
+        let mut alignment = Alignment::default();
+        for (x, y) in self.columns() {
+            alignment.push(x.map(|x| &a[x]), y.map(|y| &b[y]));
+        }
+        alignment
+    }
+
+    /// Cigar returns this script as a CIGAR string, with a as the reference and b as the
+    /// query: runs of matches and substitutions are M, inserted symbols I and deleted
+    /// symbols D. A transposition aligns two symbols on each side, so it counts as 2M
+    ///
+    /// ```
+    /// let script = lev_rs::edit_script("kitten", "sitting");
+    /// assert_eq!(script.cigar(), "6M1I");
+    /// ```
+    pub fn cigar(&self) -> String {
+        let mut cigar = String::new();
+        let mut run: Option<(char, usize)> = None;
+
+        for column in self.columns() {
+            let op = match column {
+                (Some(_), Some(_)) => 'M',
+                (None, _) => 'I',
+                (_, None) => 'D',
+            };
+            run = match run {
+                Some((last, len)) if last == op => Some((op, len + 1)),
+                Some((last, len)) => {
+                    cigar.push_str(&format!("{}{}", len, last));
+                    Some((op, 1))
+                }
+                None => Some((op, 1)),
+            };
+        }
+        if let Some((last, len)) = run {
+            cigar.push_str(&format!("{}{}", len, last));
+        }
+
+        cigar
+    }
+
+    // columns returns the positions in a and b lined up in each column of the alignment,
+    // where None is a gap. A Transpose gives two columns, with the columns of the edits
+    // between its swapped symbols in the middle
+    fn columns(&self) -> Vec<(Option<usize>, Option<usize>)> {
+        let mut columns = Vec::with_capacity(self.edits.len());
+
+        let mut i = 0;
+        while i < self.edits.len() {
+            let edit = self.edits[i];
+            i += 1;
+            match edit.op {
+                EditOp::Match | EditOp::Substitute => columns.push((Some(edit.a), Some(edit.b))),
+                EditOp::Insert => columns.push((None, Some(edit.b))),
+                EditOp::Delete => columns.push((Some(edit.a), None)),
+                EditOp::Transpose => {
+                    columns.push((Some(edit.a), Some(edit.b)));
+                    let (mut x, mut y) = (edit.a + 1, edit.b + 1);
+                    while let Some(next) = self.edits.get(i) {
+                        match next.op {
+                            EditOp::Delete if (next.a, next.b) == (x, edit.b + 1) => {
+                                columns.push((Some(x), None));
+                                x += 1;
+                            }
+                            EditOp::Insert if (next.a, next.b) == (x, y) => {
+                                columns.push((None, Some(y)));
+                                y += 1;
+                            }
+                            _ => break,
+                        }
+                        i += 1;
+                    }
+                    columns.push((Some(x), Some(y)));
+                }
+            }
+        }
+
+        columns
+    }
+}
+
+// pad fills s out to width chars, using gaps if s is empty and spaces otherwise
+fn pad(s: &str, width: usize) -> String {
+    let fill = if s.is_empty() { Alignment::GAP } else { ' ' };
+    let mut padded = s.to_string();
+    padded.extend(std::iter::repeat_n(fill, width - s.chars().count()));
+    padded
+}
+
+impl<'a> IntoIterator for &'a EditScript {
+    type Item = &'a Edit;
+    type IntoIter = std::slice::Iter<'a, Edit>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.edits.iter()
+    }
+}
+
+impl IntoIterator for EditScript {
+    type Item = Edit;
+    type IntoIter = std::vec::IntoIter<Edit>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.edits.into_iter()
+    }
+}
+
+/// Alignment is a pair of equal-width rows showing a and b lined up column by column,
+/// with gaps where a symbol was inserted or deleted
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Alignment {
+    pub a: String,
+    pub b: String,
+}
+
+impl Alignment {
+    /// The character used to fill the row of the input that has no symbol in a column
+    pub const GAP: char = '-';
+
+    // push appends one column, where None is a gap
+    fn push<T: fmt::Display>(&mut self, top: Option<&T>, bottom: Option<&T>) {
+        let top = top.map(|s| s.to_string()).unwrap_or_default();
+        let bottom = bottom.map(|s| s.to_string()).unwrap_or_default();
+
+        // Multi-char units such as graphemes are padded so the columns stay aligned
+        let width = top.chars().count().max(bottom.chars().count());
+        self.a.push_str(&pad(&top, width));
+        self.b.push_str(&pad(&bottom, width));
+    }
+}
+
+impl fmt::Display for Alignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n{}", self.a, self.b)
+    }
+}
+
+/// Edit_script returns the edits that turn a into b, counting each char as one symbol
+///
+/// ```
+/// use lev_rs::{edit_script, EditOp};
+///
+/// let ops: Vec<EditOp> = edit_script("fast", "past").iter().map(|e| e.op).collect();
+/// assert_eq!(ops, [EditOp::Substitute, EditOp::Match, EditOp::Match, EditOp::Match]);
+/// ```
+pub fn edit_script(a: &str, b: &str) -> EditScript {
+    edit_script_with(a, b, Unit::Char)
+}
+
+/// Edit_script_with returns the edits that turn a into b, counting symbols in the given unit
+pub fn edit_script_with(a: &str, b: &str, unit: Unit) -> EditScript {
+    with_units!(unit, a, b, |a, b| edit_script_slices(a, b))
+}
+
+/// Edit_script_slices returns the edits that turn sequence a into sequence b
+pub fn edit_script_slices<T: PartialEq>(a: &[T], b: &[T]) -> EditScript {
+    let matrix = levenshtein_matrix(a, b);
+
+    traceback(
+        a.len(),
+        b.len(),
+        |x, y| x == 0 && y == 0,
+        |x, y| {
+            let cell = matrix.get(x, y);
+            if x > 0 && y > 0 {
+                let diagonal = matrix.get(x - 1, y - 1);
+                if a[x - 1] == b[y - 1] && diagonal == cell {
+                    return EditOp::Match;
+                }
+                if diagonal + 1 == cell {
+                    return EditOp::Substitute;
+                }
+            }
+            if x > 0 && matrix.get(x - 1, y) + 1 == cell {
+                EditOp::Delete
+            } else {
+                EditOp::Insert
+            }
+        },
+    )
+}
+
+// traceback walks backwards through a filled DP grid from cell (x, y) until done
+// returns true, asking step which operation produced each cell along the way. Edits
+// are returned in forward order
+pub(crate) fn traceback(
+    mut x: usize,
+    mut y: usize,
+    mut done: impl FnMut(usize, usize) -> bool,
+    mut step: impl FnMut(usize, usize) -> EditOp,
+) -> EditScript {
+    let mut edits = Vec::with_capacity(x.max(y));
+
+    while !done(x, y) {
+        let op = step(x, y);
+        let (dx, dy) = match op {
+            EditOp::Match | EditOp::Substitute => (1, 1),
+            EditOp::Insert => (0, 1),
+            EditOp::Delete => (1, 0),
+            EditOp::Transpose => (2, 2),
+        };
+        x -= dx;
+        y -= dy;
+        edits.push(Edit { op, a: x, b: y });
+    }
+
+    edits.reverse();
+    EditScript::new(edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_traces_edits() {
+        use EditOp::*;
+
+        let test_cases: [(&str, &str, &[EditOp]); 7] = [
+            ("", "", &[]),
+            ("abc", "", &[Delete, Delete, Delete]),
+            ("", "ab", &[Insert, Insert]),
+            ("fast", "past", &[Substitute, Match, Match, Match]),
+            (
+                "aaaaa",
+                "baaaaa",
+                &[Insert, Match, Match, Match, Match, Match],
+            ),
+            ("aabaa", "aaaa", &[Match, Match, Delete, Match, Match]),
+            (
+                "kitten",
+                "sitting",
+                &[Substitute, Match, Match, Match, Substitute, Match, Insert],
+            ),
+        ];
+
+        for tc in test_cases.iter() {
+            let script = edit_script(tc.0, tc.1);
+            let ops: Vec<EditOp> = script.iter().map(|e| e.op).collect();
+            assert_eq!(
+                ops, tc.2,
+                "edit_script({}, {}) - got {:?}, want {:?}",
+                tc.0, tc.1, ops, tc.2
+            );
+            assert_eq!(script.distance(), crate::distance(tc.0, tc.1));
+        }
+    }
+
+    #[test]
+    fn it_records_positions() {
+        let script = edit_script("abc", "xabd");
+        let want = [
+            Edit {
+                op: EditOp::Insert,
+                a: 0,
+                b: 0,
+            },
+            Edit {
+                op: EditOp::Match,
+                a: 0,
+                b: 1,
+            },
+            Edit {
+                op: EditOp::Match,
+                a: 1,
+                b: 2,
+            },
+            Edit {
+                op: EditOp::Substitute,
+                a: 2,
+                b: 3,
+            },
+        ];
+        assert_eq!(script.edits(), want);
+    }
+
+    #[test]
+    fn it_aligns() {
+        let test_cases = [
+            ("fast", "past", "fast", "past"),
+            ("fast", "fat", "fast", "fa-t"),
+            ("ab", "xab", "-ab", "xab"),
+        ];
+
+        for tc in test_cases.iter() {
+            let a: Vec<char> = tc.0.chars().collect();
+            let b: Vec<char> = tc.1.chars().collect();
+            let alignment = edit_script(tc.0, tc.1).alignment(&a, &b);
+            assert_eq!((alignment.a.as_str(), alignment.b.as_str()), (tc.2, tc.3));
+        }
+
+        let a = ["the", "cat", "sat"];
+        let b = ["the", "sat"];
+        let alignment = edit_script_slices(&a, &b).alignment(&a, &b);
+        assert_eq!(alignment.to_string(), "thecatsat\nthe---sat");
+    }
+
+    #[test]
+    fn it_writes_cigars() {
+        let test_cases = [
+            ("", "", ""),
+            ("abc", "abc", "3M"),
+            ("abc", "", "3D"),
+            ("", "ab", "2I"),
+            ("aabaa", "aaaa", "2M1D2M"),
+            ("abc", "xabd", "1I3M"),
+        ];
+
+        for tc in test_cases.iter() {
+            let got = edit_script(tc.0, tc.1).cigar();
+            assert_eq!(
+                got, tc.2,
+                "edit_script({}, {}).cigar() - got {}, want {}",
+                tc.0, tc.1, got, tc.2
+            );
+        }
+
+        assert_eq!(crate::osa_edit_script("abcd", "bacd").cigar(), "4M");
+    }
+}
@@ -0,0 +1,171 @@
+// Extended grapheme cluster segmentation, following the rules in Unicode Standard
+// Annex #29 (https://www.unicode.org/reports/tr29/).
+
+mod tables;
+
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Category {
+    Any,
+    Cr,
+    Lf,
+    Control,
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    Prepend,
+    SpacingMark,
+    L,
+    V,
+    T,
+    Lv,
+    Lvt,
+    ExtendedPictographic,
+}
+
+// category looks up the Grapheme_Cluster_Break property of c
+pub(crate) fn category(c: char) -> Category {
+    let c = c as u32;
+    // Fast path for printable ASCII, which is by far the most common input
+    if (0x20..0x7F).contains(&c) {
+        return Category::Any;
+    }
+
+    match tables::GRAPHEME_CATEGORIES.binary_search_by(|&(lo, hi, _)| {
+        if hi < c {
+            Ordering::Less
+        } else if lo > c {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }) {
+        Ok(i) => tables::GRAPHEME_CATEGORIES[i].2,
+        Err(_) => Category::Any,
+    }
+}
+
+/// Graphemes returns an iterator over the extended grapheme clusters of s
+///
+/// ```
+/// let clusters: Vec<&str> = lev_rs::graphemes("cafe\u{301} 🇨🇦").collect();
+/// assert_eq!(clusters, ["c", "a", "f", "e\u{301}", " ", "🇨🇦"]);
+/// ```
+pub fn graphemes(s: &str) -> Graphemes<'_> {
+    Graphemes { rest: s }
+}
+
+/// Iterator over the extended grapheme clusters of a string, created by [`graphemes`]
+#[derive(Clone, Debug)]
+pub struct Graphemes<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let mut chars = self.rest.char_indices();
+        let (_, first) = chars.next()?;
+
+        let mut prev = category(first);
+        // Whether the cluster so far ends in Extended_Pictographic Extend* (GB11)
+        let mut pictographic = prev == Category::ExtendedPictographic;
+        // Whether prev is a ZWJ that follows Extended_Pictographic Extend* (GB11)
+        let mut joined_pictographic = false;
+        // Number of consecutive regional indicators ending at prev (GB12, GB13)
+        let mut regional = (prev == Category::RegionalIndicator) as usize;
+
+        let mut end = self.rest.len();
+        for (i, c) in chars {
+            let next = category(c);
+            if is_boundary(prev, next, joined_pictographic, regional) {
+                end = i;
+                break;
+            }
+
+            joined_pictographic = next == Category::Zwj && pictographic;
+            pictographic = match next {
+                Category::ExtendedPictographic => true,
+                Category::Extend => pictographic,
+                _ => false,
+            };
+            regional = if next == Category::RegionalIndicator {
+                regional + 1
+            } else {
+                0
+            };
+            prev = next;
+        }
+
+        let (cluster, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(cluster)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.rest.len().div_ceil(4), Some(self.rest.len()))
+    }
+}
+
+// is_boundary reports whether a cluster boundary falls between two adjacent code
+// points with the categories prev and next
+fn is_boundary(prev: Category, next: Category, joined_pictographic: bool, regional: usize) -> bool {
+    use Category::*;
+
+    match (prev, next) {
+        // GB3
+        (Cr, Lf) => false,
+        // GB4, GB5
+        (Cr | Lf | Control, _) | (_, Cr | Lf | Control) => true,
+        // GB6, GB7, GB8
+        (L, L | V | Lv | Lvt) | (Lv | V, V | T) | (Lvt | T, T) => false,
+        // GB9, GB9a, GB9b
+        (_, Extend | Zwj | SpacingMark) | (Prepend, _) => false,
+        // GB11
+        (Zwj, ExtendedPictographic) if joined_pictographic => false,
+        // GB12, GB13
+        (RegionalIndicator, RegionalIndicator) if regional % 2 == 1 => false,
+        // GB999
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_splits_clusters() {
+        let test_cases: [(&str, &[&str]); 10] = [
+            ("", &[]),
+            ("abc", &["a", "b", "c"]),
+            ("cafe\u{301}", &["c", "a", "f", "e\u{301}"]),
+            ("a\r\nb", &["a", "\r\n", "b"]),
+            ("\n\u{301}", &["\n", "\u{301}"]),
+            // Hangul syllable spelled as conjoining jamo L V T
+            (
+                "\u{1100}\u{1161}\u{11A8}\u{1100}",
+                &["\u{1100}\u{1161}\u{11A8}", "\u{1100}"],
+            ),
+            // Family: man, ZWJ, woman, ZWJ, girl
+            ("👨\u{200D}👩\u{200D}👧!", &["👨\u{200D}👩\u{200D}👧", "!"]),
+            // Skin tone modifiers extend the preceding emoji
+            ("👍🏽👍", &["👍🏽", "👍"]),
+            // Regional indicators pair up into flags
+            ("🇨🇦🇫🇷🇩", &["🇨🇦", "🇫🇷", "🇩"]),
+            // ZWJ only joins pictographs, not letters
+            ("a\u{200D}b", &["a\u{200D}", "b"]),
+        ];
+
+        for tc in test_cases.iter() {
+            let got: Vec<&str> = graphemes(tc.0).collect();
+            assert_eq!(
+                got, tc.1,
+                "graphemes({:?}) - got {:?}, want {:?}",
+                tc.0, got, tc.1
+            );
+        }
+    }
+}
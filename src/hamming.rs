@@ -0,0 +1,151 @@
+// Hamming distance counts the positions at which two equal-length inputs differ. Only
+// substitutions are allowed, which makes sense for fixed-width codes like barcodes or
+// hashes, where an insertion or deletion would shift every following symbol.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::Unit;
+
+/// Hamming returns the number of positions at which a and b hold different chars, or an
+/// error if they aren't the same number of chars long
+///
+/// ```
+/// assert_eq!(lev_rs::hamming("karolin", "kathrin"), Ok(3));
+/// assert!(lev_rs::hamming("karolin", "kathy").is_err());
+/// ```
+pub fn hamming(a: &str, b: &str) -> Result<usize, LengthMismatch> {
+    hamming_with(a, b, Unit::Char)
+}
+
+/// Hamming_with returns the number of positions at which a and b hold different symbols
+/// of the given unit, or an error if they aren't the same number of symbols long
+pub fn hamming_with(a: &str, b: &str, unit: Unit) -> Result<usize, LengthMismatch> {
+    with_units!(unit, a, b, |a, b| hamming_slices(a, b))
+}
+
+/// Hamming_slices returns the number of positions at which sequences a and b differ, or
+/// an error if they aren't the same length
+pub fn hamming_slices<T: PartialEq>(a: &[T], b: &[T]) -> Result<usize, LengthMismatch> {
+    check_lengths(a.len(), b.len())?;
+    Ok(a.iter().zip(b.iter()).filter(|(x, y)| x != y).count())
+}
+
+/// Hamming_bits returns the number of bits that differ between a and b, or an error if
+/// they aren't the same number of bytes long
+///
+/// ```
+/// assert_eq!(lev_rs::hamming_bits(&[0b1011_0000], &[0b0011_0001]), Ok(2));
+/// ```
+pub fn hamming_bits(a: &[u8], b: &[u8]) -> Result<usize, LengthMismatch> {
+    check_lengths(a.len(), b.len())?;
+
+    // Compare a word at a time, then whatever bytes are left over
+    let (a_words, b_words) = (a.chunks_exact(8), b.chunks_exact(8));
+    let (a_rest, b_rest) = (a_words.remainder(), b_words.remainder());
+
+    let words: usize = a_words
+        .zip(b_words)
+        .map(|(x, y)| (word(x) ^ word(y)).count_ones() as usize)
+        .sum();
+    let rest: usize = a_rest
+        .iter()
+        .zip(b_rest)
+        .map(|(x, y)| (x ^ y).count_ones() as usize)
+        .sum();
+    Ok(words + rest)
+}
+
+// word reads eight bytes as a u64; the byte order doesn't matter for counting bits
+fn word(bytes: &[u8]) -> u64 {
+    u64::from_ne_bytes(bytes.try_into().expect("chunks are eight bytes"))
+}
+
+// check_lengths returns an error unless the lengths a and b are equal
+fn check_lengths(a: usize, b: usize) -> Result<(), LengthMismatch> {
+    if a == b {
+        Ok(())
+    } else {
+        Err(LengthMismatch { a, b })
+    }
+}
+
+/// LengthMismatch is returned by the Hamming distance functions when the inputs have
+/// different lengths, counted in whatever unit was being compared
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LengthMismatch {
+    pub a: usize,
+    pub b: usize,
+}
+
+impl fmt::Display for LengthMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "inputs have different lengths ({} and {})",
+            self.a, self.b
+        )
+    }
+}
+
+impl Error for LengthMismatch {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let test_cases = [
+            ("", "", Ok(0)),
+            ("abc", "abc", Ok(0)),
+            ("abc", "abd", Ok(1)),
+            ("karolin", "kathrin", Ok(3)),
+            ("1011101", "1001001", Ok(2)),
+            ("naïve", "naive", Ok(1)),
+            ("abc", "ab", Err(LengthMismatch { a: 3, b: 2 })),
+            ("", "a", Err(LengthMismatch { a: 0, b: 1 })),
+        ];
+
+        for tc in test_cases.iter() {
+            let got = hamming(tc.0, tc.1);
+            assert_eq!(
+                got, tc.2,
+                "hamming({}, {}) - got {:?}, want {:?}",
+                tc.0, tc.1, got, tc.2
+            );
+        }
+    }
+
+    #[test]
+    fn it_counts_units() {
+        assert_eq!(hamming_with("naïve", "naive", Unit::Char), Ok(1));
+        assert_eq!(
+            hamming_with("naïve", "naive", Unit::Byte),
+            Err(LengthMismatch { a: 6, b: 5 })
+        );
+        assert_eq!(hamming_with("cafe\u{301}", "cafe", Unit::Grapheme), Ok(1));
+        assert_eq!(hamming_slices(&[1, 2, 3], &[3, 2, 1]), Ok(2));
+    }
+
+    #[test]
+    fn it_counts_bits() {
+        let a: Vec<u8> = (0..=255).collect();
+        let b: Vec<u8> = a.iter().map(|x| x.rotate_left(3) ^ 0x5a).collect();
+        for len in [0, 1, 7, 8, 9, 63, 64, 65, 256] {
+            let want: u32 = a[..len]
+                .iter()
+                .zip(&b[..len])
+                .map(|(x, y)| (x ^ y).count_ones())
+                .sum();
+            let got = hamming_bits(&a[..len], &b[..len]);
+            assert_eq!(got, Ok(want as usize), "hamming_bits of {} bytes", len);
+        }
+
+        assert_eq!(hamming_bits(&[0xff; 16], &[0; 16]), Ok(128));
+        assert_eq!(
+            hamming_bits(&[0; 3], &[0; 4]),
+            Err(LengthMismatch { a: 3, b: 4 })
+        );
+    }
+}
@@ -0,0 +1,165 @@
+// Hirschberg's algorithm recovers the same edit script as edit_script_slices, but rather
+// than keeping the full DP grid it repeatedly splits b in half, uses two single_row
+// sweeps (one forwards over the top half, one backwards over the bottom half) to find
+// where the optimal path crosses the middle row, and recurses on the two quadrants that
+// path passes through. Only two rows are ever alive, so memory is O(min(n, m)) while
+// time stays O(n * m).
+
+use crate::{edit_script_slices, single_row, Edit, EditOp, EditScript, Unit};
+
+/// Hirschberg returns the edits that turn a into b, counting each char as one symbol,
+/// using memory linear in the length of the shorter input
+///
+/// ```
+/// let script = lev_rs::hirschberg("kitten", "sitting");
+/// assert_eq!(script.distance(), 3);
+/// ```
+pub fn hirschberg(a: &str, b: &str) -> EditScript {
+    hirschberg_with(a, b, Unit::Char)
+}
+
+/// Hirschberg_with returns the edits that turn a into b, counting symbols in the given
+/// unit, using memory linear in the length of the shorter input
+pub fn hirschberg_with(a: &str, b: &str, unit: Unit) -> EditScript {
+    with_units!(unit, a, b, |a, b| hirschberg_slices(a, b))
+}
+
+/// Hirschberg_slices returns the edits that turn sequence a into sequence b, using
+/// memory linear in the length of the shorter sequence
+pub fn hirschberg_slices<T: PartialEq>(a: &[T], b: &[T]) -> EditScript {
+    // Rows run along the first input, so make that the shorter one and flip the
+    // resulting edits back around afterwards
+    if a.len() > b.len() {
+        let edits = hirschberg_slices(b, a)
+            .into_iter()
+            .map(|e| Edit {
+                op: match e.op {
+                    EditOp::Insert => EditOp::Delete,
+                    EditOp::Delete => EditOp::Insert,
+                    op => op,
+                },
+                a: e.b,
+                b: e.a,
+            })
+            .collect();
+        return EditScript::new(edits);
+    }
+
+    let mut state = State {
+        edits: Vec::with_capacity(b.len()),
+        forward: Vec::with_capacity(a.len() + 1),
+        backward: Vec::with_capacity(a.len() + 1),
+    };
+    state.split(a, b, 0, 0);
+    EditScript::new(state.edits)
+}
+
+// State holds the output and the two reusable row buffers for one hirschberg call
+struct State {
+    edits: Vec<Edit>,
+    forward: Vec<usize>,
+    backward: Vec<usize>,
+}
+
+impl State {
+    // split appends the edits turning a into b, where a and b start at offsets a_start
+    // and b_start in the original inputs
+    fn split<T: PartialEq>(&mut self, a: &[T], b: &[T], a_start: usize, b_start: usize) {
+        if b.len() <= 1 {
+            // A grid with at most two rows is no bigger than the rows we'd sweep anyway
+            let script = edit_script_slices(a, b);
+            self.edits.extend(script.into_iter().map(|e| Edit {
+                op: e.op,
+                a: e.a + a_start,
+                b: e.b + b_start,
+            }));
+            return;
+        }
+
+        let b_mid = b.len() / 2;
+        let (b_top, b_bottom) = b.split_at(b_mid);
+
+        // forward[x] is the distance between a[..x] and b_top, and backward[k] is the
+        // distance between the last k symbols of a and b_bottom
+        single_row(&mut self.forward, a.iter(), b_top.iter());
+        single_row(&mut self.backward, a.iter().rev(), b_bottom.iter().rev());
+
+        let a_mid = (0..=a.len())
+            .min_by_key(|&x| self.forward[x] + self.backward[a.len() - x])
+            .unwrap_or(0);
+
+        self.split(&a[..a_mid], b_top, a_start, b_start);
+        self.split(&a[a_mid..], b_bottom, a_start + a_mid, b_start + b_mid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // apply replays script against a, checking every position along the way, and
+    // returns the resulting sequence
+    fn apply(script: &EditScript, a: &[char], b: &[char]) -> String {
+        let (mut x, mut y) = (0, 0);
+        let mut out = String::new();
+        for edit in script {
+            assert_eq!((edit.a, edit.b), (x, y), "edit {:?} out of place", edit);
+            match edit.op {
+                EditOp::Match => {
+                    assert_eq!(a[x], b[y]);
+                    out.push(a[x]);
+                    (x, y) = (x + 1, y + 1);
+                }
+                EditOp::Substitute => {
+                    assert_ne!(a[x], b[y]);
+                    out.push(b[y]);
+                    (x, y) = (x + 1, y + 1);
+                }
+                EditOp::Insert => {
+                    out.push(b[y]);
+                    y += 1;
+                }
+                EditOp::Delete => x += 1,
+                EditOp::Transpose => unreachable!("hirschberg never transposes"),
+            }
+        }
+        assert_eq!((x, y), (a.len(), b.len()));
+        out
+    }
+
+    #[test]
+    fn it_matches_the_full_matrix() {
+        let test_cases = [
+            ("", ""),
+            ("abc", ""),
+            ("", "abc"),
+            ("fast", "past"),
+            ("kitten", "sitting"),
+            ("sitting", "kitten"),
+            ("ababab", "bababa"),
+            ("aaaaa", "baaaaa"),
+            ("aabaa", "aaaa"),
+            ("intention", "execution"),
+            ("a", "the quick brown fox"),
+            ("the quick brown fox jumps", "over the lazy dog"),
+            ("naïve café", "naive cafe"),
+        ];
+
+        for tc in test_cases.iter() {
+            let a: Vec<char> = tc.0.chars().collect();
+            let b: Vec<char> = tc.1.chars().collect();
+            let script = hirschberg(tc.0, tc.1);
+            let want = crate::distance(tc.0, tc.1);
+            assert_eq!(
+                script.distance(),
+                want,
+                "hirschberg({}, {}) - got {}, want {}",
+                tc.0,
+                tc.1,
+                script.distance(),
+                want
+            );
+            assert_eq!(apply(&script, &a, &b), tc.1);
+        }
+    }
+}
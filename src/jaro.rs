@@ -0,0 +1,211 @@
+// Jaro similarity counts the symbols a and b have in common, where a symbol only matches
+// an equal one at a nearby position, and how many of those matches are out of order.
+// Jaro-Winkler then boosts the score of pairs sharing a prefix, which suits short strings
+// like person names where typos tend to come later in the word.
+
+use crate::Unit;
+
+// MAX_PREFIX is the longest common prefix Jaro-Winkler gives credit for
+const MAX_PREFIX: usize = 4;
+
+/// Jaro returns the Jaro similarity of a and b in [0, 1], counting each char as one symbol
+///
+/// ```
+/// let got = lev_rs::jaro("martha", "marhta");
+/// assert!((got - 0.944).abs() < 0.001);
+/// ```
+pub fn jaro(a: &str, b: &str) -> f64 {
+    jaro_with(a, b, Unit::Char)
+}
+
+/// Jaro_with returns the Jaro similarity of a and b in [0, 1], counting symbols in the
+/// given unit
+pub fn jaro_with(a: &str, b: &str, unit: Unit) -> f64 {
+    with_units!(unit, a, b, |a, b| jaro_slices(a, b))
+}
+
+/// Jaro_slices returns the Jaro similarity of sequences a and b in [0, 1]
+pub fn jaro_slices<T: PartialEq>(a: &[T], b: &[T]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    // Symbols only match when they're less than half the longer length apart
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    let mut matches = 0;
+    for (x, ax) in a.iter().enumerate() {
+        let low = x.saturating_sub(window);
+        let high = (x + window + 1).min(b.len());
+        for y in low..high {
+            if !b_matched[y] && b[y] == *ax {
+                a_matched[x] = true;
+                b_matched[y] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+
+    if matches == 0 {
+        return 0.0;
+    }
+
+    // Pair up the matched symbols in order; each pair that differs is half a transposition
+    let a_matches = a.iter().zip(a_matched).filter(|(_, m)| *m).map(|(s, _)| s);
+    let b_matches = b.iter().zip(b_matched).filter(|(_, m)| *m).map(|(s, _)| s);
+    let transpositions = a_matches.zip(b_matches).filter(|(x, y)| x != y).count() / 2;
+
+    let m = matches as f64;
+    (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0
+}
+
+/// Jaro_winkler returns the Jaro-Winkler similarity of a and b in [0, 1] with the usual
+/// prefix scale of 0.1 and boost threshold of 0.7, counting each char as one symbol
+///
+/// ```
+/// let got = lev_rs::jaro_winkler("martha", "marhta");
+/// assert!((got - 0.961).abs() < 0.001);
+/// ```
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    JaroWinkler::default().similarity(a, b)
+}
+
+/// JaroWinkler holds the parameters of the Jaro-Winkler similarity. Pairs whose Jaro
+/// similarity is over boost_threshold have it raised by prefix_scale for each symbol of
+/// their common prefix, up to four, scaled by how far the score is from 1. A prefix_scale
+/// over 0.25 can raise scores past 1
+///
+/// ```
+/// use lev_rs::JaroWinkler;
+///
+/// let strict = JaroWinkler {
+///     prefix_scale: 0.05,
+///     ..JaroWinkler::default()
+/// };
+/// assert!(strict.similarity("dwayne", "duane") < lev_rs::jaro_winkler("dwayne", "duane"));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JaroWinkler {
+    pub prefix_scale: f64,
+    pub boost_threshold: f64,
+}
+
+impl Default for JaroWinkler {
+    fn default() -> Self {
+        JaroWinkler {
+            prefix_scale: 0.1,
+            boost_threshold: 0.7,
+        }
+    }
+}
+
+impl JaroWinkler {
+    /// Similarity returns the Jaro-Winkler similarity of a and b, counting each char as
+    /// one symbol
+    pub fn similarity(&self, a: &str, b: &str) -> f64 {
+        self.similarity_with(a, b, Unit::Char)
+    }
+
+    /// Similarity_with returns the Jaro-Winkler similarity of a and b, counting symbols
+    /// in the given unit
+    pub fn similarity_with(&self, a: &str, b: &str, unit: Unit) -> f64 {
+        with_units!(unit, a, b, |a, b| self.similarity_slices(a, b))
+    }
+
+    /// Similarity_slices returns the Jaro-Winkler similarity of sequences a and b
+    pub fn similarity_slices<T: PartialEq>(&self, a: &[T], b: &[T]) -> f64 {
+        let sim = jaro_slices(a, b);
+        if sim <= self.boost_threshold {
+            return sim;
+        }
+
+        let prefix = a
+            .iter()
+            .zip(b.iter())
+            .take(MAX_PREFIX)
+            .take_while(|(x, y)| x == y)
+            .count();
+        sim + prefix as f64 * self.prefix_scale * (1.0 - sim)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        // a, b, Jaro, Jaro-Winkler
+        let test_cases = [
+            ("", "", 1.0, 1.0),
+            ("abc", "", 0.0, 0.0),
+            ("", "abc", 0.0, 0.0),
+            ("abc", "abc", 1.0, 1.0),
+            ("abc", "xyz", 0.0, 0.0),
+            ("martha", "marhta", 0.944, 0.961),
+            ("dwayne", "duane", 0.822, 0.840),
+            ("dixon", "dicksonx", 0.767, 0.813),
+            ("crate", "trace", 0.733, 0.733),
+            ("jones", "johnson", 0.790, 0.832),
+        ];
+
+        for tc in test_cases.iter() {
+            let got = jaro(tc.0, tc.1);
+            assert!(
+                (got - tc.2).abs() < 0.001,
+                "jaro({}, {}) - got {}, want {}",
+                tc.0,
+                tc.1,
+                got,
+                tc.2
+            );
+
+            let got = jaro_winkler(tc.0, tc.1);
+            assert!(
+                (got - tc.3).abs() < 0.001,
+                "jaro_winkler({}, {}) - got {}, want {}",
+                tc.0,
+                tc.1,
+                got,
+                tc.3
+            );
+        }
+    }
+
+    #[test]
+    fn it_takes_parameters() {
+        let sim = jaro("martha", "marhta");
+
+        let never = JaroWinkler {
+            boost_threshold: 1.0,
+            ..JaroWinkler::default()
+        };
+        assert_eq!(never.similarity("martha", "marhta"), sim);
+
+        let strong = JaroWinkler {
+            prefix_scale: 0.25,
+            boost_threshold: 0.0,
+        };
+        let got = strong.similarity("martha", "marhta");
+        assert!((got - (sim + 0.75 * (1.0 - sim))).abs() < 1e-12);
+    }
+
+    #[test]
+    fn it_counts_units() {
+        // The combining accent is a symbol of its own unless graphemes are counted
+        let got = jaro_with("cafe\u{301}", "cafe", Unit::Grapheme);
+        assert!((got - 2.5 / 3.0).abs() < 1e-12);
+        let got = jaro_with("cafe\u{301}", "cafe", Unit::Char);
+        assert!((got - 2.8 / 3.0).abs() < 1e-12);
+        assert_eq!(
+            jaro_with("cafe\u{301}", "cafe", Unit::Byte),
+            jaro_slices("cafe\u{301}".as_bytes(), b"cafe")
+        );
+    }
+}
@@ -0,0 +1,268 @@
+// Keyboard layouts as a CostModel, so substituting a letter for one on a neighbouring key
+// (the most likely slip of a finger) is cheaper than substituting one from across the
+// keyboard.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::CostModel;
+
+const QWERTY: &str = "\
+` 1 2 3 4 5 6 7 8 9 0 - =
+   q w e r t y u i o p [ ] \\
+    a s d f g h j k l ; '
+     z x c v b n m , . /";
+
+const AZERTY: &str = "\
+² & é \" ' ( - è _ ç à ) =
+   a z e r t y u i o p ^ $
+    q s d f g h j k l m ù *
+   < w x c v b n , ; : !";
+
+const QWERTZ: &str = "\
+^ 1 2 3 4 5 6 7 8 9 0 ß ´
+   q w e r t z u i o p ü +
+    a s d f g h j k l ö ä #
+   < y x c v b n m , . -";
+
+const DVORAK: &str = "\
+` 1 2 3 4 5 6 7 8 9 0 [ ]
+   ' , . p y f g c r l / = \\
+    a o e u i d h t n s -
+     ; q j k x b m w v z";
+
+const COLEMAK: &str = "\
+` 1 2 3 4 5 6 7 8 9 0 - =
+   q w f p g j l u y ; [ ] \\
+    a r s t d h n e i o '
+     z x c v b k m , . /";
+
+// Keys this many key widths apart or more cost as much to substitute as any other pair
+const REACH: f64 = 2.0;
+
+/// Keyboard is a key layout used as a CostModel. Inserting or deleting any char costs 1,
+/// and substituting one char for another costs their distance apart on the keyboard in
+/// key widths, divided by 2 and capped at 1. Neighbouring keys on the same row therefore
+/// cost 0.5. Letters are looked up case-insensitively, and chars that aren't on the
+/// keyboard always cost 1 to substitute
+///
+/// ```
+/// use lev_rs::{weighted_distance, Keyboard};
+///
+/// let qwerty = Keyboard::qwerty();
+/// assert_eq!(weighted_distance("nap", "map", &qwerty), 0.5);
+/// assert_eq!(weighted_distance("nap", "lap", &qwerty), 1.0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyboard {
+    keys: HashMap<char, (f64, f64)>,
+}
+
+impl Keyboard {
+    /// Qwerty is the US QWERTY layout
+    pub fn qwerty() -> Keyboard {
+        Keyboard::builtin(QWERTY)
+    }
+
+    /// Azerty is the French AZERTY layout
+    pub fn azerty() -> Keyboard {
+        Keyboard::builtin(AZERTY)
+    }
+
+    /// Qwertz is the German QWERTZ layout
+    pub fn qwertz() -> Keyboard {
+        Keyboard::builtin(QWERTZ)
+    }
+
+    /// Dvorak is the US Dvorak simplified keyboard layout
+    pub fn dvorak() -> Keyboard {
+        Keyboard::builtin(DVORAK)
+    }
+
+    /// Colemak is the Colemak layout
+    pub fn colemak() -> Keyboard {
+        Keyboard::builtin(COLEMAK)
+    }
+
+    fn builtin(grid: &str) -> Keyboard {
+        Keyboard::from_grid(grid).expect("built-in layouts are valid")
+    }
+
+    /// From_grid reads a layout drawn as text. Each line is a row of keys and each
+    /// column is half a key wide, so keys are separated by single spaces and rows can be
+    /// staggered by indenting them
+    ///
+    /// ```
+    /// let pad = lev_rs::Keyboard::from_grid("7 8 9\n4 5 6\n1 2 3").unwrap();
+    /// assert_eq!(pad.key_distance('5', '8'), Some(1.0));
+    /// assert_eq!(pad.key_distance('4', '6'), Some(2.0));
+    /// ```
+    pub fn from_grid(grid: &str) -> Result<Keyboard, LayoutError> {
+        let mut keys = HashMap::new();
+
+        for (y, line) in grid.lines().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                if c.is_whitespace() {
+                    continue;
+                }
+
+                let position = (column as f64 / 2.0, y as f64);
+                if keys.insert(fold(c), position).is_some() {
+                    return Err(LayoutError::DuplicateKey(c));
+                }
+            }
+        }
+
+        if keys.is_empty() {
+            return Err(LayoutError::Empty);
+        }
+
+        Ok(Keyboard { keys })
+    }
+
+    /// Position returns the (column, row) of the key for c, measured in key widths from
+    /// the top left of the layout
+    pub fn position(&self, c: char) -> Option<(f64, f64)> {
+        self.keys.get(&fold(c)).copied()
+    }
+
+    /// Key_distance returns how far apart the keys for a and b are, in key widths
+    pub fn key_distance(&self, a: char, b: char) -> Option<f64> {
+        let (ax, ay) = self.position(a)?;
+        let (bx, by) = self.position(b)?;
+        Some((ax - bx).hypot(ay - by))
+    }
+}
+
+// fold maps c to the char used to look it up, so that shifted letters share a key
+fn fold(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) => l,
+        _ => c,
+    }
+}
+
+impl CostModel<char> for Keyboard {
+    type Cost = f64;
+
+    fn insert_cost(&self, _: &char) -> f64 {
+        1.0
+    }
+
+    fn delete_cost(&self, _: &char) -> f64 {
+        1.0
+    }
+
+    fn substitute_cost(&self, a: &char, b: &char) -> f64 {
+        match self.key_distance(*a, *b) {
+            Some(d) => (d / REACH).min(1.0),
+            None => 1.0,
+        }
+    }
+}
+
+/// LayoutError is returned by Keyboard::from_grid for a grid that doesn't describe a
+/// usable layout
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The grid has no keys
+    Empty,
+    /// The same key appears more than once
+    DuplicateKey(char),
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::Empty => write!(f, "keyboard layout has no keys"),
+            LayoutError::DuplicateKey(c) => write!(f, "key {:?} appears more than once", c),
+        }
+    }
+}
+
+impl Error for LayoutError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weighted_distance;
+
+    #[test]
+    fn it_builds_layouts() {
+        let layouts = [
+            ("qwerty", Keyboard::qwerty(), "qwertyuiop"),
+            ("azerty", Keyboard::azerty(), "azertyuiop"),
+            ("qwertz", Keyboard::qwertz(), "qwertzuiop"),
+            ("dvorak", Keyboard::dvorak(), "',.pyfgcrl"),
+            ("colemak", Keyboard::colemak(), "qwfpgjluy;"),
+        ];
+
+        for (name, keyboard, top_row) in layouts.iter() {
+            let keys: Vec<char> = top_row.chars().collect();
+            for pair in keys.windows(2) {
+                assert_eq!(
+                    keyboard.key_distance(pair[0], pair[1]),
+                    Some(1.0),
+                    "{}: {} and {} should be neighbours",
+                    name,
+                    pair[0],
+                    pair[1]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_costs_substitutions_by_distance() {
+        let qwerty = Keyboard::qwerty();
+        let test_cases = [
+            ('q', 'w', 0.5),
+            ('n', 'm', 0.5),
+            ('N', 'm', 0.5),
+            ('q', 'p', 1.0),
+            ('a', 'é', 1.0),
+            ('f', 'f', 0.0),
+        ];
+
+        for tc in test_cases.iter() {
+            let got = qwerty.substitute_cost(&tc.0, &tc.1);
+            assert_eq!(
+                got, tc.2,
+                "substitute_cost({}, {}) - got {}, want {}",
+                tc.0, tc.1, got, tc.2
+            );
+        }
+
+        // Diagonal neighbours across rows are a little further apart than keys on the same row
+        let diagonal = qwerty.substitute_cost(&'q', &'a');
+        assert!(0.5 < diagonal && diagonal < 1.0);
+    }
+
+    #[test]
+    fn it_weighs_typos() {
+        let qwerty = Keyboard::qwerty();
+        assert!(
+            weighted_distance("hello", "jello", &qwerty)
+                < weighted_distance("hello", "mello", &qwerty)
+        );
+        assert_eq!(weighted_distance("hello", "hello", &qwerty), 0.0);
+        assert_eq!(weighted_distance("hello", "hell", &qwerty), 1.0);
+
+        // Z is next to T on QWERTZ, but not on QWERTY
+        let qwertz = Keyboard::qwertz();
+        assert_eq!(weighted_distance("zoo", "too", &qwertz), 0.5);
+        assert_eq!(weighted_distance("zoo", "too", &qwerty), 1.0);
+    }
+
+    #[test]
+    fn it_rejects_bad_grids() {
+        assert_eq!(Keyboard::from_grid(""), Err(LayoutError::Empty));
+        assert_eq!(Keyboard::from_grid(" \n  "), Err(LayoutError::Empty));
+        assert_eq!(
+            Keyboard::from_grid("a b\nc A"),
+            Err(LayoutError::DuplicateKey('A'))
+        );
+    }
+}
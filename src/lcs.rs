@@ -0,0 +1,271 @@
+// Indel distance is the edit distance when only insertions and deletions are allowed, so
+// a substitution costs two edits. Every symbol outside a longest common subsequence (LCS)
+// of a and b has to be deleted from a or inserted from b, which makes the indel distance
+// len(a) + len(b) - 2 * len(lcs).
+//
+// The length of the LCS is computed with the bit-parallel algorithm of Allison and Dix,
+// as improved by Hyyrö: bit i of a vector is clear when the LCS grows between row i and
+// i + 1 of the current column, and each column is found with a single add and a few
+// bitwise operations per 64 rows.
+
+use crate::bounded::strip_common_affixes;
+use crate::edit::traceback;
+use crate::myers::{BlockPeq, Symbol};
+use crate::{EditOp, Matrix, Unit};
+
+/// Indel_distance returns the number of insertions and deletions needed to turn a into
+/// b, counting each char as one symbol
+///
+/// ```
+/// // Levenshtein would substitute f for p, which takes a deletion and an insertion here
+/// assert_eq!(lev_rs::indel_distance("fast", "past"), 2);
+/// assert_eq!(lev_rs::indel_distance("kitten", "sitting"), 5);
+/// ```
+pub fn indel_distance(a: &str, b: &str) -> usize {
+    indel_distance_with(a, b, Unit::Char)
+}
+
+/// Indel_distance_with returns the number of insertions and deletions needed to turn a
+/// into b, counting symbols in the given unit. This uses the bit-parallel algorithm, which
+/// runs in O(n * m / 64) time
+pub fn indel_distance_with(a: &str, b: &str, unit: Unit) -> usize {
+    with_units!(unit, a, b, |a, b| fast_indel_distance(a, b))
+}
+
+/// Indel_distance_slices returns the number of insertions and deletions needed to turn
+/// sequence a into sequence b
+pub fn indel_distance_slices<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    // row[x] is the distance between the first x symbols of a and the part of b seen so far
+    let mut row: Vec<usize> = (0..=a.len()).collect();
+    let mut last;
+    for (y, by) in b.iter().enumerate() {
+        (last, row[0]) = (row[0], y + 1);
+        for (x, ax) in a.iter().enumerate() {
+            let cell = if ax == by {
+                last
+            } else {
+                1 + row[x].min(row[x + 1])
+            };
+            (last, row[x + 1]) = (row[x + 1], cell);
+        }
+    }
+    row[a.len()]
+}
+
+/// Lcs returns a longest common subsequence of a and b: the longest string whose chars
+/// appear in both a and b in the same order, though not necessarily next to each other
+///
+/// ```
+/// assert_eq!(lev_rs::lcs("kitten", "sitting"), "ittn");
+/// ```
+pub fn lcs(a: &str, b: &str) -> String {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    lcs_slices(&a, &b).into_iter().collect()
+}
+
+/// Lcs_slices returns a longest common subsequence of sequences a and b
+pub fn lcs_slices<T: PartialEq + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    lcs_pairs_slices(a, b)
+        .into_iter()
+        .map(|(x, _)| a[x].clone())
+        .collect()
+}
+
+/// Lcs_pairs returns the positions of a longest common subsequence of a and b, as pairs
+/// of matching char indices into a and b in increasing order
+///
+/// ```
+/// assert_eq!(lev_rs::lcs_pairs("abcd", "acbd"), [(0, 0), (1, 2), (3, 3)]);
+/// ```
+pub fn lcs_pairs(a: &str, b: &str) -> Vec<(usize, usize)> {
+    lcs_pairs_with(a, b, Unit::Char)
+}
+
+/// Lcs_pairs_with returns the positions of a longest common subsequence of a and b, as
+/// pairs of indices into a and b counted in the given unit
+pub fn lcs_pairs_with(a: &str, b: &str, unit: Unit) -> Vec<(usize, usize)> {
+    with_units!(unit, a, b, |a, b| lcs_pairs_slices(a, b))
+}
+
+/// Lcs_pairs_slices returns the positions of a longest common subsequence of sequences a
+/// and b, as pairs of matching indices into a and b in increasing order
+pub fn lcs_pairs_slices<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let matrix = indel_matrix(a, b);
+
+    traceback(
+        a.len(),
+        b.len(),
+        |x, y| x == 0 && y == 0,
+        |x, y| {
+            let cell = matrix.get(x, y);
+            if x > 0 && y > 0 && a[x - 1] == b[y - 1] && matrix.get(x - 1, y - 1) == cell {
+                EditOp::Match
+            } else if x > 0 && matrix.get(x - 1, y) + 1 == cell {
+                EditOp::Delete
+            } else {
+                EditOp::Insert
+            }
+        },
+    )
+    .iter()
+    .filter(|e| e.op == EditOp::Match)
+    .map(|e| (e.a, e.b))
+    .collect()
+}
+
+// indel_matrix is levenshtein_matrix without substitutions, so each cell holds the indel
+// distance between a prefix of a and a prefix of b
+fn indel_matrix<T: PartialEq>(a: &[T], b: &[T]) -> Matrix {
+    let mut matrix = Matrix::new(a.len() + 1, b.len() + 1, 0);
+    for x in 0..=a.len() {
+        matrix.set(x, 0, x);
+    }
+    for y in 0..=b.len() {
+        matrix.set(0, y, y);
+    }
+
+    for y in 1..=b.len() {
+        for x in 1..=a.len() {
+            if a[x - 1] == b[y - 1] {
+                matrix.set(x, y, matrix.get(x - 1, y - 1));
+            } else {
+                let cell = 1 + matrix.get(x - 1, y).min(matrix.get(x, y - 1));
+                matrix.set(x, y, cell);
+            }
+        }
+    }
+    matrix
+}
+
+// fast_indel_distance computes the same result as indel_distance_slices, using the
+// bit-parallel LCS length with the shorter input as the pattern
+pub(crate) fn fast_indel_distance<T: Symbol>(a: &[T], b: &[T]) -> usize {
+    let (a, b) = strip_common_affixes(a, b);
+    let (pattern, text) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if pattern.is_empty() {
+        return text.len();
+    }
+
+    let mut peq = BlockPeq::default();
+    peq.fill(pattern);
+    pattern.len() + text.len() - 2 * lcs_length(&peq, pattern.len(), text)
+}
+
+// lcs_length returns the length of the LCS of the pattern peq was built from, which is
+// m symbols long, and text
+fn lcs_length<T: Symbol>(peq: &BlockPeq<T>, m: usize, text: &[T]) -> usize {
+    // Bits past the end of the pattern never match, so they stay set and don't count
+    let mut v = vec![!0u64; m.div_ceil(64)];
+
+    for symbol in text {
+        let mut carry = false;
+        for (v, &eq) in v.iter_mut().zip(peq.masks(symbol)) {
+            let u = *v & eq;
+            let (sum, c1) = v.overflowing_add(u);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            carry = c1 || c2;
+            *v = sum | (*v - u);
+        }
+    }
+
+    v.iter().map(|v| v.count_zeros() as usize).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let test_cases = [
+            ("", "", 0, ""),
+            ("abc", "", 3, ""),
+            ("", "abc", 3, ""),
+            ("abc", "abc", 0, "abc"),
+            ("fast", "past", 2, "ast"),
+            ("foo", "bar", 6, ""),
+            ("kitten", "sitting", 5, "ittn"),
+            ("ababab", "bababa", 2, "ababa"),
+            ("aabaa", "aaaa", 1, "aaaa"),
+            ("naïve", "naive", 2, "nave"),
+        ];
+
+        for tc in test_cases.iter() {
+            let got = indel_distance(tc.0, tc.1);
+            assert_eq!(
+                got, tc.2,
+                "indel_distance({}, {}) - got {}, want {}",
+                tc.0, tc.1, got, tc.2
+            );
+
+            let a: Vec<char> = tc.0.chars().collect();
+            let b: Vec<char> = tc.1.chars().collect();
+            let got = indel_distance_slices(&a, &b);
+            assert_eq!(
+                got, tc.2,
+                "indel_distance_slices({}, {}) - got {}, want {}",
+                tc.0, tc.1, got, tc.2
+            );
+
+            let got = lcs(tc.0, tc.1);
+            assert_eq!(
+                got, tc.3,
+                "lcs({}, {}) - got {}, want {}",
+                tc.0, tc.1, got, tc.3
+            );
+        }
+    }
+
+    #[test]
+    fn it_pairs_indices() {
+        let words = [
+            "", "a", "fast", "past", "kitten", "sitting", "ababab", "bababa",
+        ];
+
+        for a in words.iter() {
+            for b in words.iter() {
+                let pairs = lcs_pairs(a, b);
+                let (a_chars, b_chars): (Vec<char>, Vec<char>) =
+                    (a.chars().collect(), b.chars().collect());
+                for (i, &(x, y)) in pairs.iter().enumerate() {
+                    assert_eq!(a_chars[x], b_chars[y], "lcs_pairs({}, {})", a, b);
+                    if i > 0 {
+                        let (px, py) = pairs[i - 1];
+                        assert!(px < x && py < y, "lcs_pairs({}, {}) out of order", a, b);
+                    }
+                }
+                assert_eq!(
+                    a_chars.len() + b_chars.len() - 2 * pairs.len(),
+                    indel_distance(a, b)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_matches_the_dp_for_long_inputs() {
+        // Cover patterns spanning several words, with enough repetition to give long LCSs
+        let alphabet = ['a', 'b', 'c', 'd', 'é'];
+        let make = |len: usize, step: usize| -> String {
+            (0..len)
+                .map(|i| alphabet[(i * i * step + i / 3) % alphabet.len()])
+                .collect()
+        };
+
+        for (m, n) in [(63, 64), (64, 65), (100, 130), (200, 150), (300, 129)] {
+            let (a, b) = (make(m, 3), make(n, 7));
+            let a_chars: Vec<char> = a.chars().collect();
+            let b_chars: Vec<char> = b.chars().collect();
+            let want = indel_distance_slices(&a_chars, &b_chars);
+            assert_eq!(
+                indel_distance(&a, &b),
+                want,
+                "indel_distance of {} and {} chars",
+                m,
+                n
+            );
+            assert_eq!(lcs_pairs(&a, &b).len(), (m + n - want) / 2);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use crate::myers::Scratch;
+use crate::{single_row, single_row_distance};
+
+/// Levenshtein computes distances between many pairs of strings, keeping the buffers it
+/// decodes chars into and the tables the bit-parallel algorithm builds from one call to
+/// the next. Once they've grown to fit the longest inputs, comparing ASCII strings
+/// doesn't allocate at all
+///
+/// ```
+/// let mut lev = lev_rs::Levenshtein::new();
+/// let candidates = ["sitting", "kitchen", "mitten", "bitten"];
+///
+/// let close: Vec<&str> = candidates
+///     .iter()
+///     .copied()
+///     .filter(|c| lev.distance("kitten", c) <= 1)
+///     .collect();
+/// assert_eq!(close, ["mitten", "bitten"]);
+/// ```
+#[derive(Default)]
+pub struct Levenshtein {
+    a: Vec<char>,
+    b: Vec<char>,
+    row: Vec<usize>,
+    scratch: Scratch<char>,
+}
+
+impl Levenshtein {
+    pub fn new() -> Self {
+        Levenshtein::default()
+    }
+
+    /// Distance returns the Levenshtein distance between strings a and b, counting each
+    /// char as one symbol, as lev_rs::distance does
+    pub fn distance(&mut self, a: &str, b: &str) -> usize {
+        self.decode(a, b);
+        self.scratch
+            .distance(&self.a, &self.b, None)
+            .expect("unbounded distance always has a result")
+    }
+
+    /// Distance_within returns the Levenshtein distance between strings a and b if it is
+    /// at most max, or None otherwise, as lev_rs::distance_within does
+    pub fn distance_within(&mut self, a: &str, b: &str, max: usize) -> Option<usize> {
+        self.decode(a, b);
+        self.scratch.distance(&self.a, &self.b, Some(max))
+    }
+
+    /// Distance_slices returns the Levenshtein distance between sequences a and b, as
+    /// lev_rs::distance_slices does, reusing the same DP row each time
+    pub fn distance_slices<T: PartialEq>(&mut self, a: &[T], b: &[T]) -> usize {
+        if a.is_empty() || b.is_empty() {
+            return single_row_distance(a, b);
+        }
+
+        single_row(&mut self.row, a.iter(), b.iter());
+        self.row[a.len()]
+    }
+
+    fn decode(&mut self, a: &str, b: &str) {
+        self.a.clear();
+        self.a.extend(a.chars());
+        self.b.clear();
+        self.b.extend(b.chars());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reuses_buffers() {
+        let words = [
+            "",
+            "a",
+            "fast",
+            "past",
+            "kitten",
+            "sitting",
+            "naïve café",
+            "naive cafe",
+            "日本語",
+            "the quick brown fox jumps over the lazy dog and keeps on running for a good while",
+            "the quick brown fox jumped over the lazy dogs and kept running for quite a while",
+        ];
+
+        let mut lev = Levenshtein::new();
+        for a in words.iter() {
+            for b in words.iter() {
+                let want = crate::distance(a, b);
+                assert_eq!(lev.distance(a, b), want, "distance({}, {})", a, b);
+                assert_eq!(
+                    lev.distance_slices(a.as_bytes(), b.as_bytes()),
+                    crate::distance_slices(a.as_bytes(), b.as_bytes())
+                );
+
+                for max in [0, 1, 2, 5, 50] {
+                    let want = Some(want).filter(|&d| d <= max);
+                    assert_eq!(
+                        lev.distance_within(a, b, max),
+                        want,
+                        "distance_within({}, {}, {})",
+                        a,
+                        b,
+                        max
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,103 +1,233 @@
 use std::cmp;
 
-/// Distance returns the Levenstein distance between strings a and b
+#[macro_use]
+mod unit;
+mod affine;
+mod align;
+mod automaton;
+mod bktree;
+mod bounded;
+mod cost;
+mod damerau;
+mod edit;
+mod grapheme;
+mod hamming;
+mod hirschberg;
+mod jaro;
+mod keyboard;
+mod lcs;
+mod levenshtein;
+mod matrix;
+mod myers;
+mod query;
+mod search;
+mod similarity;
+mod substitution;
+mod substring;
+mod symspell;
+mod trie;
+
+pub use affine::{
+    affine_align, affine_align_slices, affine_score, affine_score_slices, AffineGaps,
+};
+pub use align::{
+    local_align, local_align_slices, semiglobal_align, semiglobal_align_slices, AlignMode,
+    ScoredAlignment, Scores, Scoring,
+};
+pub use automaton::LevenshteinAutomaton;
+pub use bktree::{BkTree, Metric};
+pub use bounded::{distance_within, distance_within_slices, distance_within_with};
+pub use cost::{weighted_distance, weighted_distance_slices, Cost, CostModel, UnitCost, Weights};
+pub use damerau::{
+    damerau_distance, damerau_distance_slices, damerau_distance_with, damerau_edit_script,
+    damerau_edit_script_slices, damerau_edit_script_with, osa_distance, osa_distance_slices,
+    osa_distance_with, osa_edit_script, osa_edit_script_slices, osa_edit_script_with,
+};
+pub use edit::{
+    edit_script, edit_script_slices, edit_script_with, Alignment, Edit, EditOp, EditScript,
+};
+pub use grapheme::{graphemes, Graphemes};
+pub use hamming::{hamming, hamming_bits, hamming_slices, hamming_with, LengthMismatch};
+pub use hirschberg::{hirschberg, hirschberg_slices, hirschberg_with};
+pub use jaro::{jaro, jaro_slices, jaro_winkler, jaro_with, JaroWinkler};
+pub use keyboard::{Keyboard, LayoutError};
+pub use lcs::{
+    indel_distance, indel_distance_slices, indel_distance_with, lcs, lcs_pairs, lcs_pairs_slices,
+    lcs_pairs_with, lcs_slices,
+};
+pub use levenshtein::Levenshtein;
+pub use query::Query;
+pub use search::{
+    find_approx, find_approx_slices, ApproxMatch, ApproxMatches, CharColumns, SliceColumns,
+};
+pub use similarity::{
+    normalized_similarity, normalized_similarity_slices, normalized_similarity_with, similarity,
+    Normalization,
+};
+pub use substitution::{MatrixError, SubstitutionMatrix};
+pub use substring::{
+    longest_common_substring, longest_common_substring_many, longest_common_substring_many_slices,
+    longest_common_substring_many_with, longest_common_substring_slices,
+    longest_common_substring_with, CommonSubstring,
+};
+pub use symspell::{Suggestion, SymSpell};
+pub use trie::Trie;
+pub use unit::Unit;
+
+use matrix::Matrix;
+
+/// Distance returns the Levenstein distance between strings a and b, counting each
+/// Unicode scalar value (char) as one symbol
 pub fn distance(a: &str, b: &str) -> usize {
+    distance_with(a, b, Unit::Char)
+}
+
+/// Distance_with returns the Levenshtein distance between strings a and b, counting
+/// symbols in the given unit. After trimming any common prefix and suffix, this runs
+/// Myers' bit-parallel algorithm with the shorter string as the pattern: a single machine
+/// word when it is at most 64 units long, and one word per 64-unit block otherwise
+///
+/// ```
+/// use lev_rs::{distance_with, Unit};
+///
+/// // "e" followed by a combining acute accent is one grapheme but two chars
+/// assert_eq!(distance_with("cafe\u{301}", "cafe", Unit::Byte), 2);
+/// assert_eq!(distance_with("cafe\u{301}", "cafe", Unit::Char), 1);
+/// assert_eq!(distance_with("cafe\u{301}", "cafe", Unit::Grapheme), 1);
+/// ```
+pub fn distance_with(a: &str, b: &str, unit: Unit) -> usize {
+    with_units!(unit, a, b, |a, b| myers::fast_distance(a, b))
+}
+
+/// Distance_slices returns the Levenshtein distance between two sequences of any
+/// comparable symbols, such as tokens, byte strings or symbol ids
+///
+/// ```
+/// let a = ["the", "quick", "brown", "fox"];
+/// let b = ["the", "slow", "brown", "dog", "barked"];
+/// assert_eq!(lev_rs::distance_slices(&a, &b), 3);
+/// ```
+pub fn distance_slices<T: PartialEq>(a: &[T], b: &[T]) -> usize {
     single_row_distance(a, b)
 }
 
-// Compute the Levenshtein distance between strings a and b
+/// Distance_iter returns the Levenshtein distance between the sequences produced by two
+/// iterators. Both are collected up front, so prefer distance_slices when the symbols
+/// are already in memory
+///
+/// ```
+/// let words = "one two three".split(' ');
+/// assert_eq!(lev_rs::distance_iter(words, "one three".split(' ')), 1);
+/// ```
+pub fn distance_iter<A, B, T>(a: A, b: B) -> usize
+where
+    A: IntoIterator<Item = T>,
+    B: IntoIterator<Item = T>,
+    T: PartialEq,
+{
+    let a: Vec<T> = a.into_iter().collect();
+    let b: Vec<T> = b.into_iter().collect();
+    distance_slices(&a, &b)
+}
+
+// Compute the Levenshtein distance between sequences a and b
 #[allow(dead_code)]
-fn naive_distance(a: &str, b: &str) -> usize {
-    if a.len() == 0 {
+fn naive_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    if a.is_empty() {
         return b.len();
     }
 
-    if b.len() == 0 {
+    if b.is_empty() {
         return a.len();
     }
 
-    if a.chars().nth(0) == b.chars().nth(0) {
+    if a[0] == b[0] {
         return naive_distance(&a[1..], &b[1..]);
     }
 
     1 + min3(
-        naive_distance(&a[1..], &b),
-        naive_distance(&a, &b[1..]),
+        naive_distance(&a[1..], b),
+        naive_distance(a, &b[1..]),
         naive_distance(&a[1..], &b[1..]),
     )
 }
 
 // matrix_distance computes the Levenstein distance of two words without recursion
 #[allow(dead_code)]
-fn matrix_distance(a: &str, b: &str) -> usize {
-    if a.len() == 0 {
+fn matrix_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    if a.is_empty() {
         return b.len();
     }
 
-    if b.len() == 0 {
+    if b.is_empty() {
         return a.len();
     }
 
+    levenshtein_matrix(a, b).last()
+}
+
+// levenshtein_matrix builds the full grid used by matrix_distance, where each cell holds
+// the distance between a prefix of a and a prefix of b. Keeping the whole grid around
+// lets us trace back through it to recover the edits themselves
+fn levenshtein_matrix<T: PartialEq>(a: &[T], b: &[T]) -> Matrix {
     // Produces a grid in the form
     // 0 1 2 3 4
     // 1 0 0 0 0
     // 2 0 0 0 0
     // 3 0 0 0 0
-    let mut matrix = vec![0; (a.len() + 1) * (b.len() + 1)];
-    let index = |x: usize, y: usize| -> usize { (y * (a.len() + 1)) + x };
+    let mut matrix = Matrix::new(a.len() + 1, b.len() + 1, 0);
 
     for x in 0..=a.len() {
         // Populate top row
-        matrix[index(x, 0)] = x;
+        matrix.set(x, 0, x);
     }
 
     for y in 0..=b.len() {
-        matrix[index(0, y)] = y;
+        matrix.set(0, y, y);
     }
 
     for y in 1..=b.len() {
         for x in 1..=a.len() {
-            if a.chars().nth(x - 1) == b.chars().nth(y - 1) {
-                matrix[index(x, y)] = matrix[index(x - 1, y - 1)]
+            if a[x - 1] == b[y - 1] {
+                matrix.set(x, y, matrix.get(x - 1, y - 1))
             } else {
-                matrix[index(x, y)] = 1 + min3(
-                    matrix[index(x - 1, y)],
-                    matrix[index(x, y - 1)],
-                    matrix[index(x - 1, y - 1)],
+                matrix.set(
+                    x,
+                    y,
+                    1 + min3(
+                        matrix.get(x - 1, y),
+                        matrix.get(x, y - 1),
+                        matrix.get(x - 1, y - 1),
+                    ),
                 )
             }
         }
     }
 
-    return matrix[matrix.len() - 1];
+    matrix
 }
 
 // Double row distance performs the same calulation as matrix_distance, but swaps between
 // only two rows, rather than building and maintaining the entire grid.
 #[allow(dead_code)]
-fn double_row_distance(a: &str, b: &str) -> usize {
-    if a.len() == 0 {
+fn double_row_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    if a.is_empty() {
         return b.len();
     }
 
-    if b.len() == 0 {
+    if b.is_empty() {
         return a.len();
     }
 
     let mut row1 = vec![0; a.len() + 1];
-    let mut row2 = vec![0; a.len() + 1];
-
-    // TODO: is there a way to do this with an iterator?
-    for i in 0..row2.len() {
-        row2[i] = i;
-    }
+    let mut row2: Vec<usize> = (0..=a.len()).collect();
 
-    for y in 0..b.len() {
+    for (y, by) in b.iter().enumerate() {
         (row1, row2) = (row2, row1);
         row2[0] = y + 1;
 
         for x in 0..a.len() {
-            if a.chars().nth(x) == b.chars().nth(y) {
+            if a[x] == *by {
                 row2[x + 1] = row1[x];
             } else {
                 row2[x + 1] = 1 + min3(row1[x + 1], row1[x], row2[x]);
@@ -110,38 +240,65 @@ fn double_row_distance(a: &str, b: &str) -> usize {
 
 // single_row_distance produces the same results as double_row_distance with one array,
 // and one temporary variable
-fn single_row_distance(a: &str, b: &str) -> usize {
-    if a.len() == 0 {
+fn single_row_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    if a.is_empty() {
         return b.len();
     }
 
-    if b.len() == 0 {
+    if b.is_empty() {
         return a.len();
     }
 
-    let mut row = vec![0; a.len() + 1];
-    for i in 0..row.len() {
-        row[i] = i
-    }
+    let mut row = Vec::new();
+    single_row(&mut row, a.iter(), b.iter());
+    row[a.len()]
+}
+
+// single_row runs the single_row_distance sweep and leaves its final row in row, so that
+// row[x] is the distance between the first x symbols of a and all of b. Taking iterators
+// rather than slices lets callers sweep over reversed inputs as well
+fn single_row<'t, T, A, B>(row: &mut Vec<usize>, a: A, b: B)
+where
+    T: PartialEq + 't,
+    A: Iterator<Item = &'t T> + Clone,
+    B: Iterator<Item = &'t T>,
+{
+    row.clear();
+    row.extend(0..=a.clone().count());
 
     let mut last;
 
-    for y in 0..b.len() {
+    for (y, by) in b.enumerate() {
         (last, row[0]) = (row[0], y + 1);
-        for x in 0..a.len() {
-            // println!("a: {}", a);
-            if a.chars().nth(x) == b.chars().nth(y) {
+        for (x, ax) in a.clone().enumerate() {
+            if ax == by {
                 (last, row[x + 1]) = (row[x + 1], last);
             } else {
-                // println!("row len:{}", row.len());
                 let tmp = last;
                 last = row[x + 1];
                 row[x + 1] = 1 + min3(tmp, row[x], row[x + 1]);
             }
         }
     }
+}
 
-    row[a.len()]
+// single_row_step advances a row of the single_row_distance sweep over a by one more
+// symbol of b, writing the next row into next. Every entry is capped at over, so callers
+// that only care about distances below some bound can keep rows small and comparable
+fn single_row_step<T: PartialEq>(
+    row: &[usize],
+    a: &[T],
+    by: &T,
+    over: usize,
+    next: &mut Vec<usize>,
+) {
+    next.clear();
+    next.push(cmp::min(row[0] + 1, over));
+    for (x, ax) in a.iter().enumerate() {
+        let diagonal = if ax == by { row[x] } else { row[x] + 1 };
+        let cell = min3(diagonal, row[x + 1] + 1, next[x] + 1);
+        next.push(cmp::min(cell, over));
+    }
 }
 
 fn min3<T: std::cmp::Ord>(a: T, b: T, c: T) -> T {
@@ -171,28 +328,32 @@ mod tests {
         ];
 
         for tc in test_cases.iter() {
-            let naive_result = naive_distance(tc.0, tc.1);
+            let a: Vec<char> = tc.0.chars().collect();
+            let b: Vec<char> = tc.1.chars().collect();
+            let (a, b) = (&a[..], &b[..]);
+
+            let naive_result = naive_distance(a, b);
             assert_eq!(
                 naive_result, tc.2,
                 "naive_distance({}, {}) - got {}, want {}",
                 tc.0, tc.1, naive_result, tc.2
             );
 
-            let matrix_result = matrix_distance(tc.0, tc.1);
+            let matrix_result = matrix_distance(a, b);
             assert_eq!(
                 matrix_result, tc.2,
                 "matrix_distance({}, {}) - got {}, want {}",
                 tc.0, tc.1, matrix_result, tc.2
             );
 
-            let double_row_result = double_row_distance(tc.0, tc.1);
+            let double_row_result = double_row_distance(a, b);
             assert_eq!(
                 double_row_result, tc.2,
                 "double_row_distance({}, {}) - got {}, want {}",
                 tc.0, tc.1, double_row_result, tc.2
             );
 
-            let single_row_result = single_row_distance(tc.0, tc.1);
+            let single_row_result = single_row_distance(a, b);
             assert_eq!(
                 single_row_result, tc.2,
                 "single_row_distance({}, {}) - got {}, want {}",
@@ -200,4 +361,76 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn it_counts_units() {
+        let test_cases = [
+            ("café", "cafe", Unit::Char, 1),
+            ("café", "cafe", Unit::Byte, 2),
+            ("café", "cafe", Unit::Grapheme, 1),
+            ("cafe\u{301}", "café", Unit::Char, 2),
+            ("cafe\u{301}", "café", Unit::Grapheme, 1),
+            ("naïve", "naive", Unit::Char, 1),
+            ("日本語", "日本", Unit::Char, 1),
+            ("日本語", "日本", Unit::Byte, 3),
+            (
+                "👨\u{200D}👩\u{200D}👧",
+                "👨\u{200D}👩\u{200D}👦",
+                Unit::Grapheme,
+                1,
+            ),
+            ("👨\u{200D}👩\u{200D}👧", "", Unit::Grapheme, 1),
+            ("👨\u{200D}👩\u{200D}👧", "", Unit::Char, 5),
+            ("🇨🇦🇫🇷", "🇫🇷🇨🇦", Unit::Grapheme, 2),
+        ];
+
+        for tc in test_cases.iter() {
+            let result = distance_with(tc.0, tc.1, tc.2);
+            assert_eq!(
+                result, tc.3,
+                "distance_with({}, {}, {:?}) - got {}, want {}",
+                tc.0, tc.1, tc.2, result, tc.3
+            );
+        }
+
+        assert_eq!(distance("naïve", "naive"), 1);
+    }
+
+    #[test]
+    fn it_compares_any_sequence() {
+        #[derive(PartialEq)]
+        struct Token {
+            kind: u8,
+            text: &'static str,
+        }
+
+        let a = [
+            Token {
+                kind: 0,
+                text: "let",
+            },
+            Token { kind: 1, text: "x" },
+            Token { kind: 2, text: "=" },
+        ];
+        let b = [
+            Token {
+                kind: 0,
+                text: "let",
+            },
+            Token { kind: 1, text: "y" },
+            Token { kind: 2, text: "=" },
+        ];
+        assert_eq!(distance_slices(&a, &b), 1);
+        assert_eq!(distance_slices(&a[..1], &b), 2);
+
+        let ids: Vec<u32> = vec![7, 8, 9, 10];
+        assert_eq!(distance_slices(&ids, &[8, 9, 10, 11]), 2);
+        assert_eq!(distance_slices::<u32>(&[], &ids), 4);
+
+        assert_eq!(distance_slices(b"kitten", b"sitting"), 3);
+        assert_eq!(distance_slices(&[0.5, 1.0], &[0.5, 2.0]), 1);
+
+        assert_eq!(distance_iter("kitten".bytes(), "sitting".bytes()), 3);
+        assert_eq!(distance_iter(1..10, 2..11), 2);
+    }
 }
@@ -0,0 +1,38 @@
+// Matrix is a dense grid of DP cells, stored row by row. Columns are indexed by x
+// (positions in a) and rows by y (positions in b), so a grid comparing a and b is
+// (a.len() + 1) wide and (b.len() + 1) tall.
+#[derive(Clone, Debug)]
+pub(crate) struct Matrix<V = usize> {
+    width: usize,
+    height: usize,
+    cells: Vec<V>,
+}
+
+impl<V: Copy> Matrix<V> {
+    pub(crate) fn new(width: usize, height: usize, fill: V) -> Self {
+        Matrix {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    pub(crate) fn get(&self, x: usize, y: usize) -> V {
+        self.cells[self.index(x, y)]
+    }
+
+    pub(crate) fn set(&mut self, x: usize, y: usize, value: V) {
+        let i = self.index(x, y);
+        self.cells[i] = value;
+    }
+
+    // last returns the bottom-right cell, which holds the result for the full inputs
+    pub(crate) fn last(&self) -> V {
+        self.cells[self.cells.len() - 1]
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        debug_assert!(x < self.width && y < self.height);
+        (y * self.width) + x
+    }
+}
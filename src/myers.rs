@@ -0,0 +1,535 @@
+// Myers' bit-parallel Levenshtein algorithm, in the formulation given by Hyyrö. Rather
+// than storing a column of distances it stores the vertical differences between
+// neighbouring cells (each is -1, 0 or +1) as two bit vectors, one bit per pattern
+// symbol, so a whole column of the DP grid is computed with a handful of word-sized
+// operations. Patterns up to 64 symbols long fit in a single u64; longer ones are split
+// into blocks of 64 rows that pass the horizontal difference along their bottom edge
+// down to the next block, as described in Myers' original paper.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::bounded::strip_common_affixes;
+
+// Symbol is a unit that can be looked up in a Peq table. ASCII symbols get a direct
+// lookup, anything else goes through a hash map
+pub(crate) trait Symbol: Eq + Hash + Copy {
+    fn ascii(&self) -> Option<u8>;
+}
+
+impl Symbol for u8 {
+    fn ascii(&self) -> Option<u8> {
+        self.is_ascii().then_some(*self)
+    }
+}
+
+impl Symbol for char {
+    fn ascii(&self) -> Option<u8> {
+        self.is_ascii().then_some(*self as u8)
+    }
+}
+
+impl Symbol for &str {
+    fn ascii(&self) -> Option<u8> {
+        match self.as_bytes() {
+            [c] if c.is_ascii() => Some(*c),
+            _ => None,
+        }
+    }
+}
+
+// Peq is the match table for a pattern of at most 64 symbols: the mask for a symbol has
+// bit i set wherever pattern[i] is that symbol
+pub(crate) struct Peq<T> {
+    ascii: [u64; 128],
+    other: HashMap<T, u64>,
+}
+
+impl<T: Symbol> Peq<T> {
+    // fill replaces the table with one for pattern, reusing the existing storage
+    pub(crate) fn fill(&mut self, pattern: &[T]) {
+        debug_assert!(pattern.len() <= 64);
+
+        self.ascii = [0; 128];
+        self.other.clear();
+        for (i, symbol) in pattern.iter().enumerate() {
+            match symbol.ascii() {
+                Some(c) => self.ascii[c as usize] |= 1 << i,
+                None => *self.other.entry(*symbol).or_insert(0) |= 1 << i,
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, symbol: &T) -> u64 {
+        match symbol.ascii() {
+            Some(c) => self.ascii[c as usize],
+            None => self.other.get(symbol).copied().unwrap_or(0),
+        }
+    }
+}
+
+impl<T> Default for Peq<T> {
+    fn default() -> Self {
+        Peq {
+            ascii: [0; 128],
+            other: HashMap::new(),
+        }
+    }
+}
+
+// myers_distance returns the Levenshtein distance between the pattern peq was built
+// from, which is m symbols long, and text
+pub(crate) fn myers_distance<T: Symbol>(
+    peq: &Peq<T>,
+    m: usize,
+    text: impl IntoIterator<Item = T>,
+) -> usize {
+    if m == 0 {
+        return text.into_iter().count();
+    }
+
+    // Bit i of vp (vn) is set when cell i + 1 in the current column is one more (less)
+    // than cell i. The first column counts up from 0, so every difference is +1
+    let mut vp: u64 = !0;
+    let mut vn: u64 = 0;
+    let last = 1 << (m - 1);
+    let mut score = m;
+
+    for symbol in text {
+        let eq = peq.get(&symbol);
+        let xv = eq | vn;
+        let xh = ((eq & vp).wrapping_add(vp) ^ vp) | eq;
+
+        // Horizontal differences between this column and the previous one
+        let mut hp = vn | !(xh | vp);
+        let mut hn = vp & xh;
+
+        if hp & last != 0 {
+            score += 1;
+        }
+        if hn & last != 0 {
+            score -= 1;
+        }
+
+        // The top row counts up from 0 as well, so shift in a +1
+        hp = (hp << 1) | 1;
+        hn <<= 1;
+
+        vp = hn | !(xv | hp);
+        vn = hp & xv;
+    }
+
+    score
+}
+
+// myers_distance_within returns the same distance as myers_distance when it is at most
+// max, and None otherwise. text must be n symbols long. After each column it stops as
+// soon as the distance is bound to exceed max: the last row can fall by at most 1 per
+// remaining column, and each row above it is at most 1 lower than the row below
+pub(crate) fn myers_distance_within<T: Symbol>(
+    peq: &Peq<T>,
+    m: usize,
+    n: usize,
+    text: impl IntoIterator<Item = T>,
+    max: usize,
+) -> Option<usize> {
+    if m == 0 {
+        return Some(n).filter(|&n| n <= max);
+    }
+
+    let mut vp: u64 = !0;
+    let mut vn: u64 = 0;
+    let last = 1 << (m - 1);
+    let mut score = m;
+
+    for (j, symbol) in text.into_iter().enumerate() {
+        score = score.wrapping_add_signed(
+            advance_block(&mut vp, &mut vn, peq.get(&symbol), 1, last) as isize,
+        );
+
+        // The cheapest finish is to go up the column from the last row and then along the
+        // diagonal, or straight along the last row if the text has fewer columns left
+        let remaining = n - j - 1;
+        let lowest = if remaining <= m {
+            score.saturating_sub(remaining)
+        } else {
+            (score + remaining).saturating_sub(2 * m)
+        };
+        if lowest > max {
+            return None;
+        }
+    }
+
+    Some(score).filter(|&d| d <= max)
+}
+
+// BlockPeq is the match table for a pattern of any length, split into 64 symbol blocks.
+// masks(symbol)[i] is the Peq mask of that symbol for block i
+pub(crate) struct BlockPeq<T> {
+    words: usize,
+    ascii: Vec<u64>,
+    // Masks for symbols outside ASCII are stored one after another in other_masks, and
+    // other maps each symbol to where its masks start
+    other: HashMap<T, usize>,
+    other_masks: Vec<u64>,
+    // Returned for symbols that aren't in the pattern at all
+    none: Vec<u64>,
+}
+
+impl<T: Symbol> BlockPeq<T> {
+    // fill replaces the table with one for pattern, reusing the existing storage
+    pub(crate) fn fill(&mut self, pattern: &[T]) {
+        let words = pattern.len().div_ceil(64);
+
+        self.words = words;
+        self.ascii.clear();
+        self.ascii.resize(128 * words, 0);
+        self.other.clear();
+        self.other_masks.clear();
+        self.none.clear();
+        self.none.resize(words, 0);
+
+        for (i, symbol) in pattern.iter().enumerate() {
+            let (word, bit) = (i / 64, i % 64);
+            match symbol.ascii() {
+                Some(c) => self.ascii[c as usize * words + word] |= 1 << bit,
+                None => {
+                    let next = self.other_masks.len();
+                    let start = *self.other.entry(*symbol).or_insert(next);
+                    if start == next {
+                        self.other_masks.resize(next + words, 0);
+                    }
+                    self.other_masks[start + word] |= 1 << bit;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn masks(&self, symbol: &T) -> &[u64] {
+        match symbol.ascii() {
+            Some(c) => &self.ascii[c as usize * self.words..(c as usize + 1) * self.words],
+            None => match self.other.get(symbol) {
+                Some(&start) => &self.other_masks[start..start + self.words],
+                None => &self.none,
+            },
+        }
+    }
+}
+
+impl<T> Default for BlockPeq<T> {
+    fn default() -> Self {
+        BlockPeq {
+            words: 0,
+            ascii: Vec::new(),
+            other: HashMap::new(),
+            other_masks: Vec::new(),
+            none: Vec::new(),
+        }
+    }
+}
+
+// Blocks is the per-column state of blocked_distance, kept apart from it so the vectors
+// can be reused between calls
+#[derive(Default)]
+pub(crate) struct Blocks {
+    vp: Vec<u64>,
+    vn: Vec<u64>,
+    // scores[i] is the distance in the bottom row of block i, in the current column
+    scores: Vec<usize>,
+}
+
+// blocked_distance returns the Levenshtein distance between the pattern peq was built
+// from, which is m symbols long, and text. Given a bound it returns None as soon as the
+// distance is known to exceed it, and only computes the blocks that overlap Ukkonen's
+// band of rows within max of the diagonal. The caller must already have checked that
+// the pattern and text lengths differ by no more than max.
+//
+// Blocks outside the band are never computed, so their edges are assumed to be as
+// expensive as they could possibly be: the row above the first block grows by 1 in each
+// column, and a block entering the band at the bottom starts out growing by 1 in each
+// row. This overestimates cells outside the band, but a cell within max of the diagonal
+// with a true distance of max or less is always reached by a path that stays inside the
+// band, so it is computed exactly.
+pub(crate) fn blocked_distance<T: Symbol>(
+    peq: &BlockPeq<T>,
+    blocks: &mut Blocks,
+    m: usize,
+    text: impl IntoIterator<Item = T>,
+    max: Option<usize>,
+) -> Option<usize> {
+    let bound = max.unwrap_or(usize::MAX);
+    if m == 0 {
+        return Some(text.into_iter().count()).filter(|&n| n <= bound);
+    }
+
+    let words = peq.words;
+    let height = |block: usize| cmp::min(64, m - block * 64);
+
+    let Blocks { vp, vn, scores } = blocks;
+    vp.clear();
+    vp.resize(words, !0);
+    vn.clear();
+    vn.resize(words, 0);
+    scores.clear();
+    scores.extend((0..words).map(|i| cmp::min((i + 1) * 64, m)));
+
+    let mut first = 0;
+    let mut last = cmp::min(words - 1, bound / 64);
+
+    for (j, symbol) in text.into_iter().enumerate() {
+        let column = j + 1;
+
+        // The band moves down one row per column
+        let band_last = cmp::min(words - 1, column.saturating_add(bound) / 64);
+        while last < band_last {
+            last += 1;
+            vp[last] = !0;
+            vn[last] = 0;
+            scores[last] = scores[last - 1] + height(last);
+        }
+        first = cmp::max(first, column.saturating_sub(bound.saturating_add(1)) / 64);
+
+        let masks = peq.masks(&symbol);
+        let mut carry = 1;
+        for block in first..=last {
+            let high = 1 << (height(block) - 1);
+            carry = advance_block(&mut vp[block], &mut vn[block], masks[block], carry, high);
+            scores[block] = scores[block].wrapping_add_signed(carry as isize);
+        }
+
+        if max.is_some() {
+            // Cells in a block are within height - 1 of the cell at its bottom
+            let column_min = (first..=last)
+                .map(|block| scores[block].saturating_sub(height(block) - 1))
+                .min()
+                .unwrap_or(0);
+            if column_min > bound {
+                return None;
+            }
+        }
+    }
+
+    Some(scores[words - 1]).filter(|&d| last == words - 1 && d <= bound)
+}
+
+// advance_block moves one 64 row block of the bit-parallel state on by a column. carry is
+// the horizontal difference (-1, 0 or +1) along the bottom edge of the block above, and
+// the difference along this block's bottom row, at bit high, is returned for the next
+pub(crate) fn advance_block(vp: &mut u64, vn: &mut u64, eq: u64, carry: i8, high: u64) -> i8 {
+    let xv = eq | *vn;
+    // A -1 coming in from above acts like a match in the top row of the block
+    let eq = if carry < 0 { eq | 1 } else { eq };
+    let xh = ((eq & *vp).wrapping_add(*vp) ^ *vp) | eq;
+
+    let mut hp = *vn | !(xh | *vp);
+    let mut hn = *vp & xh;
+
+    let out = if hp & high != 0 {
+        1
+    } else if hn & high != 0 {
+        -1
+    } else {
+        0
+    };
+
+    hp <<= 1;
+    hn <<= 1;
+    match carry {
+        1 => hp |= 1,
+        -1 => hn |= 1,
+        _ => {}
+    }
+
+    *vp = hn | !(xv | hp);
+    *vn = hp & xv;
+    out
+}
+
+// Scratch holds everything the bit-parallel algorithms allocate, so that callers
+// computing many distances can reuse it from one call to the next
+pub(crate) struct Scratch<T> {
+    peq: Peq<T>,
+    block_peq: BlockPeq<T>,
+    blocks: Blocks,
+}
+
+impl<T: Symbol> Scratch<T> {
+    // distance computes the same result as distance_within_slices, or distance_slices if
+    // there's no bound, using the shorter input as the pattern
+    pub(crate) fn distance(&mut self, a: &[T], b: &[T], max: Option<usize>) -> Option<usize> {
+        let bound = max.unwrap_or(usize::MAX);
+        if a.len().abs_diff(b.len()) > bound {
+            return None;
+        }
+
+        let (a, b) = strip_common_affixes(a, b);
+        let (pattern, text) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+
+        if pattern.len() <= 64 {
+            self.peq.fill(pattern);
+            Some(myers_distance(
+                &self.peq,
+                pattern.len(),
+                text.iter().copied(),
+            ))
+            .filter(|&d| d <= bound)
+        } else {
+            self.block_peq.fill(pattern);
+            blocked_distance(
+                &self.block_peq,
+                &mut self.blocks,
+                pattern.len(),
+                text.iter().copied(),
+                max,
+            )
+        }
+    }
+}
+
+impl<T> Default for Scratch<T> {
+    fn default() -> Self {
+        Scratch {
+            peq: Peq::default(),
+            block_peq: BlockPeq::default(),
+            blocks: Blocks::default(),
+        }
+    }
+}
+
+// fast_distance computes the same result as single_row_distance, using the bit-parallel
+// algorithm with the shorter input as the pattern
+pub(crate) fn fast_distance<T: Symbol>(a: &[T], b: &[T]) -> usize {
+    Scratch::default()
+        .distance(a, b, None)
+        .expect("unbounded distance always has a result")
+}
+
+// fast_distance_within computes the same result as distance_within_slices, using the
+// bit-parallel algorithm with the shorter input as the pattern
+pub(crate) fn fast_distance_within<T: Symbol>(a: &[T], b: &[T], max: usize) -> Option<usize> {
+    Scratch::default().distance(a, b, Some(max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::single_row_distance;
+
+    // random_string returns a deterministic pseudo-random string of len symbols drawn from
+    // alphabet
+    fn random_string(seed: &mut u64, len: usize, alphabet: &[char]) -> String {
+        (0..len)
+            .map(|_| {
+                // xorshift64
+                *seed ^= *seed << 13;
+                *seed ^= *seed >> 7;
+                *seed ^= *seed << 17;
+                alphabet[(*seed % alphabet.len() as u64) as usize]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_matches_single_row_distance() {
+        let test_cases = [
+            ("", ""),
+            ("", "abc"),
+            ("abc", ""),
+            ("fast", "past"),
+            ("foo", "bar"),
+            ("aaaaaa", "bbb"),
+            ("ababab", "bababa"),
+            ("aabaa", "aaaa"),
+            ("kitten", "sitting"),
+            ("naïve café", "naive cafe"),
+            ("日本語のテキスト", "日本のテキスト"),
+        ];
+
+        for tc in test_cases.iter() {
+            let a: Vec<char> = tc.0.chars().collect();
+            let b: Vec<char> = tc.1.chars().collect();
+            let want = single_row_distance(&a, &b);
+
+            let mut peq = Peq::default();
+            peq.fill(&a);
+            let got = myers_distance(&peq, a.len(), b.iter().copied());
+            assert_eq!(
+                got, want,
+                "myers_distance({}, {}) - got {}, want {}",
+                tc.0, tc.1, got, want
+            );
+
+            let got = fast_distance(&a, &b);
+            assert_eq!(
+                got, want,
+                "fast_distance({}, {}) - got {}, want {}",
+                tc.0, tc.1, got, want
+            );
+
+            for max in 0..=want + 1 {
+                let got = myers_distance_within(&peq, a.len(), b.len(), b.iter().copied(), max);
+                let want = Some(want).filter(|&d| d <= max);
+                assert_eq!(
+                    got, want,
+                    "myers_distance_within({}, {}, {}) - got {:?}, want {:?}",
+                    tc.0, tc.1, max, got, want
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_handles_word_sized_patterns() {
+        let mut seed = 0x2545f4914f6cdd1d;
+        let alphabets: [&[char]; 3] = [
+            &['a', 'b'],
+            &['a', 'c', 'g', 't'],
+            &['x', 'y', 'é', '日', '🦀'],
+        ];
+
+        for alphabet in alphabets.iter() {
+            for len in [1, 2, 31, 63, 64, 65, 100, 128, 129, 300] {
+                let a: Vec<char> = random_string(&mut seed, len, alphabet).chars().collect();
+                let b: Vec<char> = random_string(&mut seed, len + len / 3, alphabet)
+                    .chars()
+                    .collect();
+                let want = single_row_distance(&a, &b);
+
+                assert_eq!(fast_distance(&a, &b), want, "a: {:?}, b: {:?}", a, b);
+                assert_eq!(fast_distance(&b, &a), want, "a: {:?}, b: {:?}", b, a);
+            }
+        }
+    }
+
+    #[test]
+    fn it_bounds_blocked_distance() {
+        let mut seed = 0x9e3779b97f4a7c15;
+        let alphabet = ['a', 'c', 'g', 't'];
+
+        for len in [65, 130, 200, 500] {
+            let a: Vec<char> = random_string(&mut seed, len, &alphabet).chars().collect();
+
+            // Mutate a copy of a in a few places, so the distance is small relative to len
+            let mut b = a.clone();
+            for i in (7..len).step_by(41) {
+                b[i] = if b[i] == 'a' { 'c' } else { 'a' };
+            }
+            b.insert(len / 2, 't');
+            b.remove(len / 3);
+
+            let want = single_row_distance(&a, &b);
+            let mut peq = BlockPeq::default();
+            peq.fill(&a);
+            let mut blocks = Blocks::default();
+            for max in [0, 1, want / 2, want - 1, want, want + 1, want * 2, len * 2] {
+                let got =
+                    blocked_distance(&peq, &mut blocks, a.len(), b.iter().copied(), Some(max));
+                let expected = Some(want).filter(|&d| d <= max);
+                assert_eq!(got, expected, "len {}, max {}", len, max);
+                assert_eq!(fast_distance_within(&a, &b, max), expected);
+                assert_eq!(fast_distance_within(&b, &a, max), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::myers::{
+    blocked_distance, myers_distance, myers_distance_within, BlockPeq, Blocks, Peq,
+};
+
+/// Query is a pattern prepared for comparing against many candidates. The pattern is
+/// decoded and its bit-parallel match tables are built once in Query::new, so each
+/// comparison only has to walk the candidate's chars
+///
+/// ```
+/// use lev_rs::Query;
+///
+/// let query = Query::new("kitten");
+/// assert_eq!(query.distance_to("sitting"), 3);
+/// assert_eq!(query.within("sitting", 2), None);
+///
+/// let matches = query.best_matches(["mitten", "kitchen", "kitten", "smitten"], 2);
+/// assert_eq!(matches, [("kitten", 0), ("mitten", 1), ("kitchen", 2), ("smitten", 2)]);
+/// ```
+pub struct Query {
+    pattern: Vec<char>,
+    tables: Tables,
+}
+
+// Tables are the match masks for the pattern, using the single word algorithm whenever
+// the pattern is short enough. The per-column block state for long patterns is made
+// afresh on each comparison, so a Query can be shared between threads
+enum Tables {
+    Word(Box<Peq<char>>),
+    Blocks(BlockPeq<char>),
+}
+
+impl Query {
+    pub fn new(pattern: &str) -> Self {
+        let pattern: Vec<char> = pattern.chars().collect();
+
+        let tables = if pattern.len() <= 64 {
+            let mut peq = Box::<Peq<char>>::default();
+            peq.fill(&pattern);
+            Tables::Word(peq)
+        } else {
+            let mut peq = BlockPeq::default();
+            peq.fill(&pattern);
+            Tables::Blocks(peq)
+        };
+
+        Query { pattern, tables }
+    }
+
+    /// Pattern returns the chars of the pattern this query was built from
+    pub fn pattern(&self) -> &[char] {
+        &self.pattern
+    }
+
+    /// Distance_to returns the Levenshtein distance between the pattern and candidate,
+    /// counting each char as one symbol
+    pub fn distance_to(&self, candidate: &str) -> usize {
+        self.distance(candidate, None)
+            .expect("unbounded distance always has a result")
+    }
+
+    /// Within returns the Levenshtein distance between the pattern and candidate if it is
+    /// at most max, or None otherwise
+    pub fn within(&self, candidate: &str, max: usize) -> Option<usize> {
+        // Every char is at least one byte, and at most four
+        let (low, high) = (candidate.len().div_ceil(4), candidate.len());
+        if low > self.pattern.len() + max || high + max < self.pattern.len() {
+            return None;
+        }
+
+        let n = candidate.chars().count();
+        if n.abs_diff(self.pattern.len()) > max {
+            return None;
+        }
+
+        match &self.tables {
+            Tables::Word(peq) => {
+                myers_distance_within(peq, self.pattern.len(), n, candidate.chars(), max)
+            }
+            Tables::Blocks(..) => self.distance(candidate, Some(max)),
+        }
+    }
+
+    /// Best_matches returns every candidate within max edits of the pattern along with
+    /// its distance, closest first. Candidates at the same distance keep their order
+    pub fn best_matches<S, I>(&self, candidates: I, max: usize) -> Vec<(S, usize)>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = S>,
+    {
+        let mut matches: Vec<(S, usize)> = candidates
+            .into_iter()
+            .filter_map(|c| self.within(c.as_ref(), max).map(|d| (c, d)))
+            .collect();
+        matches.sort_by_key(|&(_, d)| d);
+        matches
+    }
+
+    fn distance(&self, candidate: &str, max: Option<usize>) -> Option<usize> {
+        let m = self.pattern.len();
+        match &self.tables {
+            Tables::Word(peq) => {
+                let d = myers_distance(peq, m, candidate.chars());
+                Some(d).filter(|&d| max.is_none_or(|max| d <= max))
+            }
+            Tables::Blocks(peq) => {
+                blocked_distance(peq, &mut Blocks::default(), m, candidate.chars(), max)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_compares_candidates() {
+        let long = "the quick brown fox jumps over the lazy dog, then naps in the afternoon sun";
+        let patterns = ["", "a", "kitten", "naïve café", long];
+        let candidates = [
+            "",
+            "a",
+            "sitting",
+            "kitten",
+            "naive cafe",
+            "日本語",
+            "the quick brown fox jumped over a lazy dog, then napped in the afternoon sun",
+        ];
+
+        for pattern in patterns.iter() {
+            let query = Query::new(pattern);
+            for candidate in candidates.iter() {
+                let want = crate::distance(pattern, candidate);
+                let got = query.distance_to(candidate);
+                assert_eq!(
+                    got, want,
+                    "Query::new({}).distance_to({}) - got {}, want {}",
+                    pattern, candidate, got, want
+                );
+
+                for max in [0, 1, 3, 10, 100] {
+                    let want = Some(want).filter(|&d| d <= max);
+                    let got = query.within(candidate, max);
+                    assert_eq!(
+                        got, want,
+                        "Query::new({}).within({}, {}) - got {:?}, want {:?}",
+                        pattern, candidate, max, got, want
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_is_shareable_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Query>();
+    }
+
+    #[test]
+    fn it_ranks_matches() {
+        let query = Query::new("color");
+        let candidates = vec![
+            String::from("colour"),
+            String::from("collar"),
+            String::from("color"),
+            String::from("dolor"),
+            String::from("cooler"),
+            String::from("flavour"),
+        ];
+
+        let matches = query.best_matches(candidates, 2);
+        let got: Vec<(&str, usize)> = matches.iter().map(|(s, d)| (s.as_str(), *d)).collect();
+        assert_eq!(
+            got,
+            [
+                ("color", 0),
+                ("colour", 1),
+                ("dolor", 1),
+                ("collar", 2),
+                ("cooler", 2)
+            ]
+        );
+
+        assert!(query.best_matches(["xxxxxxxxxx"], 3).is_empty());
+    }
+}
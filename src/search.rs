@@ -0,0 +1,442 @@
+// Approximate search finds every place in a text where a pattern occurs with at most k
+// edits, using Sellers' variant of the DP: the pattern runs down the rows and the text
+// along the columns as usual, but the top row is all zeros rather than counting up, so a
+// match can start anywhere in the text for free. The bottom cell of each column is then
+// the fewest edits needed to match the whole pattern against some substring of the text
+// ending there.
+//
+// For strings the columns are computed with Myers' bit-parallel algorithm, which only
+// needs the horizontal difference coming in along the top row to be 0 rather than +1.
+// Once a column ends within k, a second, backwards DP finds where that match starts. It
+// never looks back more than len(pattern) + k symbols, so only that many of the most
+// recent symbols are kept and the text is read as a stream.
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::iter::Enumerate;
+use std::slice;
+use std::str::CharIndices;
+
+use crate::myers::{advance_block, BlockPeq, Symbol};
+use crate::single_row_step;
+
+/// ApproxMatch is an occurrence of a pattern in a text: text[start..end] is distance
+/// edits away from the pattern
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ApproxMatch {
+    pub start: usize,
+    pub end: usize,
+    pub distance: usize,
+}
+
+/// Find_approx returns an iterator over the places where pattern occurs in text with at
+/// most k edits, counting each char as one symbol. Positions are byte offsets into text,
+/// so text[m.start..m.end] is the matched substring. There's one match for each end
+/// position in order, starting wherever the fewest edits are needed and, among those, as
+/// late as possible
+///
+/// ```
+/// let text = "a haystack with a neddle in it";
+/// let matches: Vec<_> = lev_rs::find_approx("needle", text, 1)
+///     .map(|m| (&text[m.start..m.end], m.distance))
+///     .collect();
+/// assert_eq!(matches, [("neddle", 1)]);
+/// ```
+pub fn find_approx<'t>(
+    pattern: &str,
+    text: &'t str,
+    k: usize,
+) -> ApproxMatches<'t, char, CharColumns<'t>> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let columns = CharColumns {
+        text: text.char_indices(),
+        scanner: BitScanner::new(&pattern),
+    };
+    ApproxMatches::new(Cow::Owned(pattern), columns, k)
+}
+
+/// Find_approx_slices returns an iterator over the places where sequence pattern occurs
+/// in sequence text with at most k edits, as find_approx does for strings. Positions are
+/// indices into text
+pub fn find_approx_slices<'t, T: PartialEq + Clone>(
+    pattern: &'t [T],
+    text: &'t [T],
+    k: usize,
+) -> ApproxMatches<'t, T, SliceColumns<'t, T>> {
+    let columns = SliceColumns {
+        text: text.iter().enumerate(),
+        scanner: ColumnScanner::new(pattern.len()),
+    };
+    ApproxMatches::new(Cow::Borrowed(pattern), columns, k)
+}
+
+/// ApproxMatches is the iterator returned by find_approx and find_approx_slices. It reads
+/// the text one symbol at a time as matches are asked for
+pub struct ApproxMatches<'t, T: Clone, C> {
+    pattern: Cow<'t, [T]>,
+    reversed: Vec<T>,
+    columns: C,
+    k: usize,
+    // The position just past the last symbol read, or None before the empty prefix of
+    // the text has been checked
+    end: Option<usize>,
+    // The last len(pattern) + k symbols read, each with the position it starts at
+    recent: VecDeque<(usize, T)>,
+    // Buffers for finding where a match starts
+    row: Vec<usize>,
+    next: Vec<usize>,
+}
+
+impl<'t, T: Clone, C> ApproxMatches<'t, T, C> {
+    fn new(pattern: Cow<'t, [T]>, columns: C, k: usize) -> Self {
+        // Every column is within len(pattern) edits, so any larger k finds the same
+        // matches
+        let k = k.min(pattern.len());
+        let reversed = pattern.iter().rev().cloned().collect();
+        let recent = VecDeque::with_capacity(pattern.len().saturating_add(k));
+        ApproxMatches {
+            pattern,
+            reversed,
+            columns,
+            k,
+            end: None,
+            recent,
+            row: Vec::new(),
+            next: Vec::new(),
+        }
+    }
+}
+
+impl<T: PartialEq + Clone, C> ApproxMatches<'_, T, C> {
+    // start returns where the shortest substring of the text ending at end and distance
+    // edits from the pattern begins, by running the DP backwards over the recent symbols
+    fn start(&mut self, end: usize, distance: usize) -> usize {
+        let over = distance + 1;
+        let window = self
+            .recent
+            .len()
+            .min(self.pattern.len().saturating_add(distance));
+
+        self.row.clear();
+        self.row
+            .extend((0..=self.reversed.len()).map(|x| x.min(over)));
+        for j in 0..=window {
+            if self.row[self.reversed.len()] <= distance {
+                return match j {
+                    0 => end,
+                    _ => self.recent[self.recent.len() - j].0,
+                };
+            }
+            if j == window {
+                break;
+            }
+            single_row_step(
+                &self.row,
+                &self.reversed,
+                &self.recent[self.recent.len() - j - 1].1,
+                over,
+                &mut self.next,
+            );
+            std::mem::swap(&mut self.row, &mut self.next);
+        }
+        unreachable!("a match ending at {} has a start within the window", end)
+    }
+}
+
+impl<T: PartialEq + Clone, C: Columns<T>> Iterator for ApproxMatches<'_, T, C> {
+    type Item = ApproxMatch;
+
+    fn next(&mut self) -> Option<ApproxMatch> {
+        let kept = self.pattern.len().saturating_add(self.k);
+        loop {
+            let (end, distance) = match self.end {
+                // Matching the pattern against nothing deletes all of it
+                None => (0, self.pattern.len()),
+                Some(start) => {
+                    let (end, symbol, distance) = self.columns.advance(&self.pattern)?;
+                    if self.recent.len() == kept {
+                        self.recent.pop_front();
+                    }
+                    if kept > 0 {
+                        self.recent.push_back((start, symbol));
+                    }
+                    (end, distance)
+                }
+            };
+            self.end = Some(end);
+
+            if distance <= self.k {
+                let start = self.start(end, distance);
+                return Some(ApproxMatch {
+                    start,
+                    end,
+                    distance,
+                });
+            }
+        }
+    }
+}
+
+// Columns reads a text one symbol at a time, computing the next column of Sellers' DP.
+// advance returns the position just past the symbol, the symbol itself and the bottom
+// cell of the column, or None at the end of the text
+trait Columns<T> {
+    fn advance(&mut self, pattern: &[T]) -> Option<(usize, T, usize)>;
+}
+
+/// CharColumns is the text of a find_approx search, read a char at a time with the
+/// bit-parallel algorithm
+pub struct CharColumns<'t> {
+    text: CharIndices<'t>,
+    scanner: BitScanner<char>,
+}
+
+impl Columns<char> for CharColumns<'_> {
+    fn advance(&mut self, _pattern: &[char]) -> Option<(usize, char, usize)> {
+        let (i, c) = self.text.next()?;
+        Some((i + c.len_utf8(), c, self.scanner.advance(&c)))
+    }
+}
+
+/// SliceColumns is the text of a find_approx_slices search, read a symbol at a time with
+/// one column of the DP
+pub struct SliceColumns<'t, T> {
+    text: Enumerate<slice::Iter<'t, T>>,
+    scanner: ColumnScanner,
+}
+
+impl<T: PartialEq + Clone> Columns<T> for SliceColumns<'_, T> {
+    fn advance(&mut self, pattern: &[T]) -> Option<(usize, T, usize)> {
+        let (i, symbol) = self.text.next()?;
+        Some((i + 1, symbol.clone(), self.scanner.advance(pattern, symbol)))
+    }
+}
+
+// BitScanner is the bit-parallel scanner, with the pattern split into 64 row blocks
+struct BitScanner<T> {
+    peq: BlockPeq<T>,
+    vp: Vec<u64>,
+    vn: Vec<u64>,
+    // The bit for the last row of the last block
+    high: u64,
+    score: usize,
+}
+
+impl<T: Symbol> BitScanner<T> {
+    fn new(pattern: &[T]) -> Self {
+        let mut peq = BlockPeq::default();
+        peq.fill(pattern);
+        let words = pattern.len().div_ceil(64);
+        BitScanner {
+            peq,
+            vp: vec![!0; words],
+            vn: vec![0; words],
+            high: 1 << (pattern.len().saturating_sub(1) % 64),
+            score: pattern.len(),
+        }
+    }
+
+    // advance computes the next column for symbol, returning its bottom cell
+    fn advance(&mut self, symbol: &T) -> usize {
+        let masks = self.peq.masks(symbol);
+        let words = masks.len();
+
+        // The top row is all zeros, so nothing changes along it from column to column
+        let mut carry = 0;
+        for (block, &eq) in masks.iter().enumerate() {
+            let high = if block + 1 == words {
+                self.high
+            } else {
+                1 << 63
+            };
+            carry = advance_block(&mut self.vp[block], &mut self.vn[block], eq, carry, high);
+        }
+
+        self.score = self.score.wrapping_add_signed(carry as isize);
+        self.score
+    }
+}
+
+// ColumnScanner is Sellers' DP, keeping one column of it
+struct ColumnScanner {
+    column: Vec<usize>,
+}
+
+impl ColumnScanner {
+    fn new(m: usize) -> Self {
+        ColumnScanner {
+            column: (0..=m).collect(),
+        }
+    }
+
+    // advance computes the next column for symbol, returning its bottom cell
+    fn advance<T: PartialEq>(&mut self, pattern: &[T], symbol: &T) -> usize {
+        // last holds the cell up and to the left of the one being computed
+        let mut last = self.column[0];
+        for (i, p) in pattern.iter().enumerate() {
+            let cell = if p == symbol {
+                last
+            } else {
+                1 + last.min(self.column[i]).min(self.column[i + 1])
+            };
+            last = self.column[i + 1];
+            self.column[i + 1] = cell;
+        }
+        self.column[pattern.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // brute_force finds, for every end position, the fewest edits between pattern and any
+    // substring of text ending there
+    fn brute_force(pattern: &[char], text: &[char], k: usize) -> Vec<(usize, usize)> {
+        (0..=text.len())
+            .filter_map(|end| {
+                let d = (0..=end)
+                    .map(|start| crate::distance_slices(pattern, &text[start..end]))
+                    .min()
+                    .unwrap();
+                Some((end, d)).filter(|&(_, d)| d <= k)
+            })
+            .collect()
+    }
+
+    // in_chars converts the byte offsets of a match found by find_approx in text to char
+    // offsets, as find_approx_slices reports them
+    fn in_chars(text: &str, m: &ApproxMatch) -> ApproxMatch {
+        ApproxMatch {
+            start: text[..m.start].chars().count(),
+            end: text[..m.end].chars().count(),
+            distance: m.distance,
+        }
+    }
+
+    #[test]
+    fn it_agrees_with_brute_force() {
+        let test_cases = [
+            ("", "abc"),
+            ("abc", ""),
+            ("abc", "abc"),
+            ("abc", "xxabcxxabxcxx"),
+            ("needle", "a haystack with a neddle in it and a noodle"),
+            ("ababa", "abababbabaababa"),
+            ("naïve", "a naive or naïve café"),
+        ];
+
+        for tc in test_cases.iter() {
+            let pattern: Vec<char> = tc.0.chars().collect();
+            let text: Vec<char> = tc.1.chars().collect();
+            for k in 0..=3 {
+                let want = brute_force(&pattern, &text, k);
+
+                let fast: Vec<ApproxMatch> = find_approx(tc.0, tc.1, k)
+                    .map(|m| in_chars(tc.1, &m))
+                    .collect();
+                let slow: Vec<ApproxMatch> = find_approx_slices(&pattern, &text, k).collect();
+                assert_eq!(fast, slow, "find_approx({}, {}, {})", tc.0, tc.1, k);
+
+                let got: Vec<(usize, usize)> = fast.iter().map(|m| (m.end, m.distance)).collect();
+                assert_eq!(
+                    got, want,
+                    "find_approx({}, {}, {}) - got {:?}, want {:?}",
+                    tc.0, tc.1, k, got, want
+                );
+
+                for m in fast.iter() {
+                    // The start is the latest one at the match's distance
+                    let d = crate::distance_slices(&pattern, &text[m.start..m.end]);
+                    assert_eq!(
+                        d, m.distance,
+                        "find_approx({}, {}, {}) at {:?}",
+                        tc.0, tc.1, k, m
+                    );
+                    for later in m.start + 1..=m.end {
+                        assert!(crate::distance_slices(&pattern, &text[later..m.end]) > m.distance);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_searches_with_long_patterns() {
+        // Patterns over 64 chars use several blocks in the bit-parallel scanner
+        let pattern =
+            "the quick brown fox jumps over the lazy dog and then takes a long nap in the sun";
+        let text = format!(
+            "intro text {} more text {} end",
+            "the quick brown fox jumped over the lazy dog and then took a long nap in the sun",
+            pattern
+        );
+
+        let pattern_chars: Vec<char> = pattern.chars().collect();
+        let text_chars: Vec<char> = text.chars().collect();
+        for k in [0, 2, 5, 10] {
+            let fast: Vec<ApproxMatch> = find_approx(pattern, &text, k)
+                .map(|m| in_chars(&text, &m))
+                .collect();
+            let slow: Vec<ApproxMatch> =
+                find_approx_slices(&pattern_chars, &text_chars, k).collect();
+            assert_eq!(fast, slow, "find_approx with k = {}", k);
+        }
+
+        let exact: Vec<ApproxMatch> = find_approx(pattern, &text, 0).collect();
+        let start = text.len() - 4 - pattern.len();
+        assert_eq!(
+            exact,
+            [ApproxMatch {
+                start,
+                end: start + pattern.len(),
+                distance: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn it_iterates_lazily() {
+        let mut matches = find_approx("ab", "ab ab ab", 0);
+        assert_eq!(
+            matches.next(),
+            Some(ApproxMatch {
+                start: 0,
+                end: 2,
+                distance: 0
+            })
+        );
+        assert_eq!(matches.end, Some(2));
+        assert_eq!(matches.count(), 2);
+    }
+
+    #[test]
+    fn it_reports_byte_offsets() {
+        let text = "日本語 café cafe";
+        let got: Vec<&str> = find_approx("café", text, 0)
+            .chain(find_approx("cafe", text, 0))
+            .map(|m| &text[m.start..m.end])
+            .collect();
+        assert_eq!(got, ["café", "cafe"]);
+
+        let matches: Vec<(usize, usize)> = find_approx("cafe", text, 1)
+            .map(|m| (m.start, m.end))
+            .collect();
+        assert_eq!(matches, [(10, 13), (10, 15), (16, 19), (16, 20)]);
+    }
+
+    #[test]
+    fn it_caps_k_at_the_pattern_length() {
+        let text = "xxabcxx";
+        let want: Vec<ApproxMatch> = find_approx("abc", text, 3).collect();
+        assert_eq!(want.len(), text.len() + 1);
+        for k in [4, 1 << 40, usize::MAX] {
+            let got: Vec<ApproxMatch> = find_approx("abc", text, k).collect();
+            assert_eq!(got, want, "find_approx(abc, {}, {})", text, k);
+
+            let pattern: Vec<char> = "abc".chars().collect();
+            let text: Vec<char> = text.chars().collect();
+            assert_eq!(find_approx_slices(&pattern, &text, k).count(), want.len());
+        }
+    }
+}
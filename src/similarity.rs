@@ -0,0 +1,168 @@
+// Similarity scores scale an edit distance by the lengths of the inputs, giving a number
+// in [0, 1] that can be compared across pairs of strings of different lengths: 1 means
+// the inputs are equal and 0 means they have nothing in common.
+
+use crate::lcs::fast_indel_distance;
+use crate::myers::{fast_distance, Symbol};
+use crate::{distance_slices, indel_distance_slices, Unit};
+
+/// Normalization chooses how an edit distance is scaled into a similarity score
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Normalization {
+    /// 1 - d / max(len(a), len(b)), where d is the Levenshtein distance. No more than
+    /// max(len(a), len(b)) edits are ever needed, so this spans the whole range from 0 to 1
+    /// and is the most intuitive choice, but 1 minus it is not a metric
+    #[default]
+    MaxLength,
+    /// 1 - d / (len(a) + len(b)), where d is the indel distance, which counts a
+    /// substitution as a deletion plus an insertion. This is 2 * lcs / (len(a) + len(b)),
+    /// the ratio used by difflib-style matchers
+    Indel,
+    /// 1 - 2d / (len(a) + len(b) + d), where d is the Levenshtein distance. This is Yujian
+    /// and Bo's normalization, and unlike the others 1 minus it satisfies the triangle
+    /// inequality, so it can be used anywhere a metric is needed
+    YujianBo,
+}
+
+/// Similarity returns 1 - d / max(len(a), len(b)) where d is the Levenshtein distance
+/// between a and b, counting each char as one symbol
+///
+/// ```
+/// assert_eq!(lev_rs::similarity("kitten", "sitting"), 1.0 - 3.0 / 7.0);
+/// assert_eq!(lev_rs::similarity("", ""), 1.0);
+/// ```
+pub fn similarity(a: &str, b: &str) -> f64 {
+    normalized_similarity(a, b, Normalization::MaxLength)
+}
+
+/// Normalized_similarity returns the similarity of a and b in [0, 1], scaled as chosen by
+/// normalization, counting each char as one symbol
+///
+/// ```
+/// use lev_rs::{normalized_similarity, Normalization};
+///
+/// assert_eq!(normalized_similarity("fast", "past", Normalization::MaxLength), 0.75);
+/// assert_eq!(normalized_similarity("fast", "past", Normalization::Indel), 0.75);
+/// assert_eq!(normalized_similarity("fast", "past", Normalization::YujianBo), 1.0 - 2.0 / 9.0);
+/// ```
+pub fn normalized_similarity(a: &str, b: &str, normalization: Normalization) -> f64 {
+    normalized_similarity_with(a, b, normalization, Unit::Char)
+}
+
+/// Normalized_similarity_with returns the similarity of a and b in [0, 1], scaled as
+/// chosen by normalization, counting symbols in the given unit
+pub fn normalized_similarity_with(
+    a: &str,
+    b: &str,
+    normalization: Normalization,
+    unit: Unit,
+) -> f64 {
+    with_units!(unit, a, b, |a, b| fast_similarity(a, b, normalization))
+}
+
+/// Normalized_similarity_slices returns the similarity of sequences a and b in [0, 1],
+/// scaled as chosen by normalization
+pub fn normalized_similarity_slices<T: PartialEq>(
+    a: &[T],
+    b: &[T],
+    normalization: Normalization,
+) -> f64 {
+    let d = match normalization {
+        Normalization::Indel => indel_distance_slices(a, b),
+        Normalization::MaxLength | Normalization::YujianBo => distance_slices(a, b),
+    };
+    normalize(d, a.len(), b.len(), normalization)
+}
+
+// fast_similarity is normalized_similarity_slices using the bit-parallel distances
+fn fast_similarity<T: Symbol>(a: &[T], b: &[T], normalization: Normalization) -> f64 {
+    let d = match normalization {
+        Normalization::Indel => fast_indel_distance(a, b),
+        Normalization::MaxLength | Normalization::YujianBo => fast_distance(a, b),
+    };
+    normalize(d, a.len(), b.len(), normalization)
+}
+
+// normalize scales the distance d between inputs of lengths m and n into a similarity
+fn normalize(d: usize, m: usize, n: usize, normalization: Normalization) -> f64 {
+    let denominator = match normalization {
+        Normalization::MaxLength => m.max(n),
+        Normalization::Indel => m + n,
+        Normalization::YujianBo => m + n + d,
+    };
+    if denominator == 0 {
+        // Two empty inputs are identical
+        return 1.0;
+    }
+
+    let numerator = match normalization {
+        Normalization::YujianBo => 2 * d,
+        Normalization::MaxLength | Normalization::Indel => d,
+    };
+    1.0 - numerator as f64 / denominator as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_normalizes() {
+        // a, b, max length, indel, Yujian-Bo
+        let test_cases = [
+            ("", "", 1.0, 1.0, 1.0),
+            ("abc", "", 0.0, 0.0, 0.0),
+            ("", "abc", 0.0, 0.0, 0.0),
+            ("abc", "abc", 1.0, 1.0, 1.0),
+            ("abc", "xyz", 0.0, 0.0, 1.0 - 6.0 / 9.0),
+            (
+                "kitten",
+                "sitting",
+                1.0 - 3.0 / 7.0,
+                1.0 - 5.0 / 13.0,
+                1.0 - 6.0 / 16.0,
+            ),
+            ("ab", "ba", 0.0, 0.5, 1.0 - 4.0 / 6.0),
+            ("naïve", "naive", 0.8, 0.8, 1.0 - 2.0 / 11.0),
+        ];
+
+        for tc in test_cases.iter() {
+            let want = [tc.2, tc.3, tc.4];
+            let normalizations = [
+                Normalization::MaxLength,
+                Normalization::Indel,
+                Normalization::YujianBo,
+            ];
+            for (normalization, want) in normalizations.iter().zip(want) {
+                let got = normalized_similarity(tc.0, tc.1, *normalization);
+                assert!(
+                    (got - want).abs() < 1e-12,
+                    "normalized_similarity({}, {}, {:?}) - got {}, want {}",
+                    tc.0,
+                    tc.1,
+                    normalization,
+                    got,
+                    want
+                );
+
+                let a: Vec<char> = tc.0.chars().collect();
+                let b: Vec<char> = tc.1.chars().collect();
+                assert_eq!(normalized_similarity_slices(&a, &b, *normalization), got);
+            }
+        }
+    }
+
+    #[test]
+    fn it_counts_units() {
+        let got = normalized_similarity_with(
+            "cafe\u{301}",
+            "cafe",
+            Normalization::MaxLength,
+            Unit::Grapheme,
+        );
+        assert_eq!(got, 0.75);
+        let got =
+            normalized_similarity_with("cafe\u{301}", "cafe", Normalization::MaxLength, Unit::Char);
+        assert_eq!(got, 0.8);
+    }
+}
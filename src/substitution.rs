@@ -0,0 +1,301 @@
+// Substitution matrices as a Scoring, giving every pair of symbols a score of its own
+// rather than one score for all matches and another for all mismatches. They are the
+// usual way to score protein alignments, where some amino acids stand in for others far
+// more often than chance would suggest, so aligning them should count in favour.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::Scoring;
+
+// BLOSUM62 in the NCBI layout, as distributed with BLAST
+const BLOSUM62: &str = "\
+   A  R  N  D  C  Q  E  G  H  I  L  K  M  F  P  S  T  W  Y  V  B  Z  X  *
+A  4 -1 -2 -2  0 -1 -1  0 -2 -1 -1 -1 -1 -2 -1  1  0 -3 -2  0 -2 -1  0 -4
+R -1  5  0 -2 -3  1  0 -2  0 -3 -2  2 -1 -3 -2 -1 -1 -3 -2 -3 -1  0 -1 -4
+N -2  0  6  1 -3  0  0  0  1 -3 -3  0 -2 -3 -2  1  0 -4 -2 -3  3  0 -1 -4
+D -2 -2  1  6 -3  0  2 -1 -1 -3 -4 -1 -3 -3 -1  0 -1 -4 -3 -3  4  1 -1 -4
+C  0 -3 -3 -3  9 -3 -4 -3 -3 -1 -1 -3 -1 -2 -3 -1 -1 -2 -2 -1 -3 -3 -2 -4
+Q -1  1  0  0 -3  5  2 -2  0 -3 -2  1  0 -3 -1  0 -1 -2 -1 -2  0  3 -1 -4
+E -1  0  0  2 -4  2  5 -2  0 -3 -3  1 -2 -3 -1  0 -1 -3 -2 -2  1  4 -1 -4
+G  0 -2  0 -1 -3 -2 -2  6 -2 -4 -4 -2 -3 -3 -2  0 -2 -2 -3 -3 -1 -2 -1 -4
+H -2  0  1 -1 -3  0  0 -2  8 -3 -3 -1 -2 -1 -2 -1 -2 -2  2 -3  0  0 -1 -4
+I -1 -3 -3 -3 -1 -3 -3 -4 -3  4  2 -3  1  0 -3 -2 -1 -3 -1  3 -3 -3 -1 -4
+L -1 -2 -3 -4 -1 -2 -3 -4 -3  2  4 -2  2  0 -3 -2 -1 -2 -1  1 -4 -3 -1 -4
+K -1  2  0 -1 -3  1  1 -2 -1 -3 -2  5 -1 -3 -1  0 -1 -3 -2 -2  0  1 -1 -4
+M -1 -1 -2 -3 -1  0 -2 -3 -2  1  2 -1  5  0 -2 -1 -1 -1 -1  1 -3 -1 -1 -4
+F -2 -3 -3 -3 -2 -3 -3 -3 -1  0  0 -3  0  6 -4 -2 -2  1  3 -1 -3 -3 -1 -4
+P -1 -2 -2 -1 -3 -1 -1 -2 -2 -3 -3 -1 -2 -4  7 -1 -1 -4 -3 -2 -2 -1 -2 -4
+S  1 -1  1  0 -1  0  0  0 -1 -2 -2  0 -1 -2 -1  4  1 -3 -2 -2  0  0  0 -4
+T  0 -1  0 -1 -1 -1 -1 -2 -2 -1 -1 -1 -1 -2 -1  1  5 -2 -2  0 -1 -1  0 -4
+W -3 -3 -4 -4 -2 -2 -3 -2 -2 -3 -2 -3 -1  1 -4 -3 -2 11  2 -3 -4 -3 -2 -4
+Y -2 -2 -2 -3 -2 -1 -2 -3  2 -1 -1 -2 -1  3 -3 -2 -2  2  7 -1 -3 -2 -1 -4
+V  0 -3 -3 -3 -1 -2 -2 -3 -3  3  1 -2  1 -1 -2 -2  0 -3 -1  4 -3 -2 -1 -4
+B -2 -1  3  4 -3  0  1 -1  0 -3 -4  0 -3 -3 -2  0 -1 -4 -3 -3  4  1 -1 -4
+Z -1  0  0  1 -3  3  4 -2  0 -3 -3  1 -1 -3 -1  0 -1 -3 -2 -2  1  4 -1 -4
+X  0 -1 -1 -1 -2 -1 -1 -1 -1 -1 -1 -1 -1 -1 -2  0  0 -2 -1 -1 -1 -1 -1 -4
+* -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4 -4  1";
+
+/// SubstitutionMatrix scores each pair of symbols from an alphabet with its own entry in
+/// a table, and every gap the same. Symbols are compared exactly, so a table in upper
+/// case needs sequences in upper case too, and symbols outside the alphabet score the
+/// lowest entry in the table against anything
+///
+/// ```
+/// use lev_rs::{local_align, SubstitutionMatrix};
+///
+/// let blosum62 = SubstitutionMatrix::blosum62(-8);
+/// let aligned = local_align("HEAGAWGHEE", "PAWHEAE", &blosum62);
+/// assert_eq!((aligned.score, aligned.script.cigar()), (20, "2M1D2M".to_string()));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubstitutionMatrix {
+    // The symbols labelling the rows and columns, sorted, and their positions in the table
+    symbols: Vec<(char, usize)>,
+    // scores[i * n + j] is the score of the i'th symbol of the table against the j'th
+    scores: Vec<i32>,
+    n: usize,
+    gap: i32,
+    lowest: i32,
+}
+
+impl SubstitutionMatrix {
+    /// Blosum62 is the BLOSUM62 matrix for amino acids, using the one letter codes in
+    /// upper case, with each gap scoring gap
+    pub fn blosum62(gap: i32) -> SubstitutionMatrix {
+        SubstitutionMatrix::from_table(BLOSUM62, gap).expect("built-in matrices are valid")
+    }
+
+    /// From_table reads a matrix in the layout used by NCBI and EMBOSS: a header line of
+    /// symbols, then a line for each of them starting with the symbol and followed by its
+    /// scores against the header's symbols in order. Blank lines and lines starting with
+    /// # are skipped
+    ///
+    /// ```
+    /// use lev_rs::SubstitutionMatrix;
+    ///
+    /// let dna = SubstitutionMatrix::from_table(
+    ///     "# transitions score higher than transversions
+    ///        A  C  G  T
+    ///     A  2 -2  0 -2
+    ///     C -2  2 -2  0
+    ///     G  0 -2  2 -2
+    ///     T -2  0 -2  2",
+    ///     -3,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(dna.pair_score('A', 'G'), 0);
+    /// ```
+    pub fn from_table(table: &str, gap: i32) -> Result<SubstitutionMatrix, MatrixError> {
+        let mut lines = table
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let header: Vec<char> = match lines.next() {
+            Some((line, header)) => header
+                .split_whitespace()
+                .map(|token| symbol(token).ok_or(MatrixError::BadRow(line)))
+                .collect::<Result<_, _>>()?,
+            None => return Err(MatrixError::Empty),
+        };
+        let n = header.len();
+
+        let mut symbols: Vec<(char, usize)> = header.iter().copied().zip(0..).collect();
+        symbols.sort_unstable();
+        if let Some(pair) = symbols.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(MatrixError::DuplicateSymbol(pair[0].0));
+        }
+
+        // Rows may come in any order, so each is placed by its label
+        let mut scores = vec![0; n * n];
+        let mut seen = vec![false; n];
+        for (line, row) in lines {
+            let mut tokens = row.split_whitespace();
+            let label = tokens.next().and_then(symbol);
+            let i = match label.and_then(|c| find(&symbols, c)) {
+                Some(i) if !seen[i] => i,
+                _ => return Err(MatrixError::BadRow(line)),
+            };
+            seen[i] = true;
+
+            let row: Vec<i32> = tokens
+                .map(|token| token.parse().map_err(|_| MatrixError::BadRow(line)))
+                .collect::<Result<_, _>>()?;
+            if row.len() != n {
+                return Err(MatrixError::BadRow(line));
+            }
+            scores[i * n..(i + 1) * n].copy_from_slice(&row);
+        }
+
+        if let Some(i) = seen.iter().position(|&seen| !seen) {
+            return Err(MatrixError::MissingRow(header[i]));
+        }
+
+        let lowest = scores.iter().copied().min().unwrap_or(0);
+        Ok(SubstitutionMatrix {
+            symbols,
+            scores,
+            n,
+            gap,
+            lowest,
+        })
+    }
+
+    /// Pair_score returns the score of aligning a against b
+    pub fn pair_score(&self, a: char, b: char) -> i32 {
+        match (find(&self.symbols, a), find(&self.symbols, b)) {
+            (Some(i), Some(j)) => self.scores[i * self.n + j],
+            _ => self.lowest,
+        }
+    }
+}
+
+// symbol returns the only char in token, if it has just one
+fn symbol(token: &str) -> Option<char> {
+    let mut chars = token.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+// find returns the position in the table of c
+fn find(symbols: &[(char, usize)], c: char) -> Option<usize> {
+    symbols
+        .binary_search_by_key(&c, |&(c, _)| c)
+        .ok()
+        .map(|found| symbols[found].1)
+}
+
+impl Scoring<char> for SubstitutionMatrix {
+    fn score(&self, a: &char, b: &char) -> i32 {
+        self.pair_score(*a, *b)
+    }
+
+    fn gap(&self) -> i32 {
+        self.gap
+    }
+}
+
+// Sequences are often held as bytes, one per residue or base
+impl Scoring<u8> for SubstitutionMatrix {
+    fn score(&self, a: &u8, b: &u8) -> i32 {
+        self.pair_score(char::from(*a), char::from(*b))
+    }
+
+    fn gap(&self) -> i32 {
+        self.gap
+    }
+}
+
+/// MatrixError is returned by SubstitutionMatrix::from_table for a table that doesn't
+/// describe a usable matrix
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MatrixError {
+    /// The table has no header line
+    Empty,
+    /// The same symbol appears more than once in the header
+    DuplicateSymbol(char),
+    /// The line with this number doesn't hold a score for each symbol in the header,
+    /// is labelled with a symbol not in the header, or repeats an earlier row
+    BadRow(usize),
+    /// The header has a symbol with no row of its own
+    MissingRow(char),
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatrixError::Empty => write!(f, "substitution matrix has no symbols"),
+            MatrixError::DuplicateSymbol(c) => {
+                write!(f, "symbol {:?} appears more than once", c)
+            }
+            MatrixError::BadRow(line) => write!(f, "line {} is not a row of the matrix", line),
+            MatrixError::MissingRow(c) => write!(f, "symbol {:?} has no row", c),
+        }
+    }
+}
+
+impl Error for MatrixError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_blosum62() {
+        let blosum62 = SubstitutionMatrix::blosum62(-4);
+        assert_eq!(blosum62.symbols.len(), 24);
+
+        for &(a, _) in blosum62.symbols.iter() {
+            for &(b, _) in blosum62.symbols.iter() {
+                assert_eq!(
+                    blosum62.pair_score(a, b),
+                    blosum62.pair_score(b, a),
+                    "blosum62 isn't symmetric at {}, {}",
+                    a,
+                    b
+                );
+            }
+        }
+
+        let test_cases = [
+            ('A', 'A', 4),
+            ('W', 'W', 11),
+            ('C', 'C', 9),
+            ('I', 'V', 3),
+            ('W', 'P', -4),
+            ('*', '*', 1),
+            ('a', 'A', -4),
+        ];
+
+        for tc in test_cases.iter() {
+            let got = blosum62.pair_score(tc.0, tc.1);
+            assert_eq!(
+                got, tc.2,
+                "pair_score({}, {}) - got {}, want {}",
+                tc.0, tc.1, got, tc.2
+            );
+            assert_eq!(
+                Scoring::<u8>::score(&blosum62, &(tc.0 as u8), &(tc.1 as u8)),
+                tc.2
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_bad_tables() {
+        let test_cases = [
+            ("", MatrixError::Empty),
+            ("# just a comment", MatrixError::Empty),
+            ("A A\nA 1 1", MatrixError::DuplicateSymbol('A')),
+            ("A B\nA 1 0\nB 0", MatrixError::BadRow(3)),
+            ("A B\nA 1 0\nC 0 1", MatrixError::BadRow(3)),
+            ("A B\nA 1 0\nA 1 0", MatrixError::BadRow(3)),
+            ("A B\n\nA 1 x", MatrixError::BadRow(3)),
+            ("AB C\nAB 1", MatrixError::BadRow(1)),
+            ("A B\nB 0 1", MatrixError::MissingRow('A')),
+        ];
+
+        for tc in test_cases.iter() {
+            let got = SubstitutionMatrix::from_table(tc.0, -1);
+            assert_eq!(
+                got.as_ref(),
+                Err(&tc.1),
+                "from_table({:?}) - got {:?}, want {:?}",
+                tc.0,
+                got,
+                tc.1
+            );
+        }
+
+        // Rows can come in any order
+        let matrix = SubstitutionMatrix::from_table("A B\nB -1 2\nA 3 -1", -1).unwrap();
+        assert_eq!(
+            (matrix.pair_score('A', 'A'), matrix.pair_score('B', 'B')),
+            (3, 2)
+        );
+    }
+}
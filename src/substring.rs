@@ -0,0 +1,377 @@
+// Longest common substring finds the longest run of symbols that appears, contiguously,
+// in every input. Rather than the O(n * m) DP it builds a suffix automaton of the first
+// input: the smallest automaton accepting exactly that input's suffixes, with at most
+// 2n states. Each state stands for a set of substrings that end at the same positions,
+// the longest of which is len symbols long, and its suffix link leads to the state of
+// the longest suffix that ends somewhere else. Walking each other input through the
+// automaton then gives, for every state, the longest of its substrings that input also
+// contains, in time linear in the total length.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::Unit;
+
+/// CommonSubstring is a substring shared by several inputs: it is len symbols long and
+/// starts at `starts[i]` in input i
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommonSubstring {
+    pub starts: Vec<usize>,
+    pub len: usize,
+}
+
+/// Longest_common_substring returns the longest substring of both a and b, with char
+/// offsets of where it starts in each, or None if they have no char in common. Ties go
+/// to the substring occurring first in a
+///
+/// ```
+/// let common = lev_rs::longest_common_substring("xabcdy", "zzabcd").unwrap();
+/// assert_eq!(common.starts, [1, 2]);
+/// assert_eq!(common.len, 4);
+/// ```
+pub fn longest_common_substring(a: &str, b: &str) -> Option<CommonSubstring> {
+    longest_common_substring_with(a, b, Unit::Char)
+}
+
+/// Longest_common_substring_with returns the longest substring of both a and b, with
+/// offsets of where it starts in each, counting symbols in the given unit
+pub fn longest_common_substring_with(a: &str, b: &str, unit: Unit) -> Option<CommonSubstring> {
+    with_units!(unit, a, b, |a, b| longest_common_substring_slices(a, b))
+}
+
+/// Longest_common_substring_slices returns the longest run of symbols found in both
+/// sequence a and sequence b, with where it starts in each
+pub fn longest_common_substring_slices<T: Eq + Hash + Clone>(
+    a: &[T],
+    b: &[T],
+) -> Option<CommonSubstring> {
+    longest_common_substring_many_slices(&[a, b])
+}
+
+/// Longest_common_substring_many returns the longest substring of every one of inputs,
+/// with char offsets of where it starts in each, or None if there isn't one
+///
+/// ```
+/// let inputs = ["the quick brown fox", "a quick brown dog", "quick brownies"];
+/// let common = lev_rs::longest_common_substring_many(&inputs).unwrap();
+/// assert_eq!(common.starts, [4, 2, 0]);
+/// assert_eq!(common.len, "quick brown".len());
+/// ```
+pub fn longest_common_substring_many(inputs: &[&str]) -> Option<CommonSubstring> {
+    longest_common_substring_many_with(inputs, Unit::Char)
+}
+
+/// Longest_common_substring_many_with returns the longest substring of every one of
+/// inputs, with offsets of where it starts in each, counting symbols in the given unit
+pub fn longest_common_substring_many_with(inputs: &[&str], unit: Unit) -> Option<CommonSubstring> {
+    match unit {
+        Unit::Byte => {
+            let inputs: Vec<&[u8]> = inputs.iter().map(|s| s.as_bytes()).collect();
+            longest_common_substring_many_slices(&inputs)
+        }
+        Unit::Char => {
+            let inputs: Vec<Vec<char>> = inputs.iter().map(|s| s.chars().collect()).collect();
+            let inputs: Vec<&[char]> = inputs.iter().map(|s| &s[..]).collect();
+            longest_common_substring_many_slices(&inputs)
+        }
+        Unit::Grapheme => {
+            let inputs: Vec<Vec<&str>> = inputs
+                .iter()
+                .map(|s| crate::graphemes(s).collect())
+                .collect();
+            let inputs: Vec<&[&str]> = inputs.iter().map(|s| &s[..]).collect();
+            longest_common_substring_many_slices(&inputs)
+        }
+    }
+}
+
+/// Longest_common_substring_many_slices returns the longest run of symbols found in
+/// every one of inputs, with where it starts in each
+pub fn longest_common_substring_many_slices<T: Eq + Hash + Clone>(
+    inputs: &[&[T]],
+) -> Option<CommonSubstring> {
+    let (first, rest) = inputs.split_first()?;
+    let automaton = SuffixAutomaton::new(first);
+
+    // common[v] is the longest substring of state v found in every input so far, and
+    // ends[i][v] is where it ends in rest[i]
+    let mut common: Vec<usize> = automaton.states.iter().map(|s| s.len).collect();
+    let mut ends = Vec::with_capacity(rest.len());
+    for input in rest {
+        let found = automaton.longest_matches(input);
+        for (common, &(len, _)) in common.iter_mut().zip(found.iter()) {
+            *common = (*common).min(len);
+        }
+        ends.push(found);
+    }
+
+    // The root state is the empty string, so skip it when looking for the longest
+    let (state, len) = common
+        .iter()
+        .copied()
+        .enumerate()
+        .skip(1)
+        .fold((0, 0), |best, (v, len)| {
+            let earlier =
+                automaton.states[v].first_end - len < automaton.states[best.0].first_end - best.1;
+            if len > best.1 || (len == best.1 && len > 0 && earlier) {
+                (v, len)
+            } else {
+                best
+            }
+        });
+    if len == 0 {
+        return None;
+    }
+
+    let mut starts = Vec::with_capacity(inputs.len());
+    starts.push(automaton.states[state].first_end - len);
+    starts.extend(ends.iter().map(|found| found[state].1 - len));
+    Some(CommonSubstring { starts, len })
+}
+
+// State is a node of a SuffixAutomaton. first_end is the position just past the end of
+// the first occurrence of its substrings in the input the automaton was built from
+struct State<T> {
+    len: usize,
+    link: Option<usize>,
+    next: HashMap<T, usize>,
+    first_end: usize,
+}
+
+struct SuffixAutomaton<T> {
+    states: Vec<State<T>>,
+}
+
+impl<T: Eq + Hash + Clone> SuffixAutomaton<T> {
+    // new builds the automaton for input one symbol at a time, using the standard online
+    // construction
+    fn new(input: &[T]) -> Self {
+        let mut states = Vec::with_capacity(2 * input.len() + 1);
+        states.push(State {
+            len: 0,
+            link: None,
+            next: HashMap::new(),
+            first_end: 0,
+        });
+        let mut last = 0;
+
+        for (i, symbol) in input.iter().enumerate() {
+            let cur = states.len();
+            states.push(State {
+                len: states[last].len + 1,
+                link: None,
+                next: HashMap::new(),
+                first_end: i + 1,
+            });
+
+            // Every suffix of the input so far can now be extended by symbol
+            let mut p = Some(last);
+            while let Some(v) = p {
+                if states[v].next.contains_key(symbol) {
+                    break;
+                }
+                states[v].next.insert(symbol.clone(), cur);
+                p = states[v].link;
+            }
+
+            states[cur].link = match p {
+                None => Some(0),
+                Some(v) => {
+                    let q = states[v].next[symbol];
+                    if states[v].len + 1 == states[q].len {
+                        Some(q)
+                    } else {
+                        // q also holds longer substrings that don't end here, so split
+                        // off the shorter ones into a clone
+                        let clone = states.len();
+                        states.push(State {
+                            len: states[v].len + 1,
+                            link: states[q].link,
+                            next: states[q].next.clone(),
+                            first_end: states[q].first_end,
+                        });
+
+                        let mut p = Some(v);
+                        while let Some(v) = p {
+                            if states[v].next.get(symbol) != Some(&q) {
+                                break;
+                            }
+                            states[v].next.insert(symbol.clone(), clone);
+                            p = states[v].link;
+                        }
+                        states[q].link = Some(clone);
+                        Some(clone)
+                    }
+                }
+            };
+            last = cur;
+        }
+
+        SuffixAutomaton { states }
+    }
+
+    // longest_matches walks input through the automaton and returns, for each state, the
+    // length of the longest of its substrings found in input and the position just past
+    // where it ends, or (0, 0) if input contains none of them
+    fn longest_matches(&self, input: &[T]) -> Vec<(usize, usize)> {
+        let mut found = vec![(0, 0); self.states.len()];
+
+        let (mut v, mut len) = (0, 0);
+        for (i, symbol) in input.iter().enumerate() {
+            // Drop symbols from the front of the match until it can be extended
+            while v != 0 && !self.states[v].next.contains_key(symbol) {
+                v = self.states[v].link.expect("only the root has no link");
+                len = self.states[v].len;
+            }
+            match self.states[v].next.get(symbol) {
+                Some(&next) => {
+                    v = next;
+                    len += 1;
+                }
+                None => continue,
+            }
+            if len > found[v].0 {
+                found[v] = (len, i + 1);
+            }
+        }
+
+        // A match in a state also contains every substring of its suffix link's state,
+        // ending at the same place. Visit longer states first so this passes all the way
+        // down each chain of links
+        let mut order: Vec<usize> = (1..self.states.len()).collect();
+        order.sort_by_key(|&v| std::cmp::Reverse(self.states[v].len));
+        for v in order {
+            let link = self.states[v].link.expect("only the root has no link");
+            let (len, end) = found[v];
+            if len > 0 && found[link].0 < self.states[link].len {
+                found[link] = (self.states[link].len, end);
+            }
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // brute_force finds the length of the longest common substring of a and b by trying
+    // every pair of starting positions
+    fn brute_force(a: &[char], b: &[char]) -> usize {
+        let mut best = 0;
+        for x in 0..a.len() {
+            for y in 0..b.len() {
+                let len = a[x..]
+                    .iter()
+                    .zip(&b[y..])
+                    .take_while(|(p, q)| p == q)
+                    .count();
+                best = best.max(len);
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn it_works() {
+        let test_cases = [
+            ("", "", None),
+            ("abc", "", None),
+            ("abc", "xyz", None),
+            ("abc", "abc", Some((vec![0, 0], 3))),
+            ("xabcdy", "zzabcd", Some((vec![1, 2], 4))),
+            ("kitten", "sitting", Some((vec![1, 1], 3))),
+            ("ababab", "bababa", Some((vec![0, 1], 5))),
+            ("banana", "ananas", Some((vec![1, 0], 5))),
+            ("naïve café", "cafés naïves", Some((vec![0, 6], 5))),
+        ];
+
+        for tc in test_cases.iter() {
+            let want =
+                tc.2.clone()
+                    .map(|(starts, len)| CommonSubstring { starts, len });
+            let got = longest_common_substring(tc.0, tc.1);
+            assert_eq!(
+                got, want,
+                "longest_common_substring({}, {}) - got {:?}, want {:?}",
+                tc.0, tc.1, got, want
+            );
+        }
+    }
+
+    #[test]
+    fn it_agrees_with_brute_force() {
+        let words = [
+            "",
+            "a",
+            "abracadabra",
+            "cadabraabra",
+            "mississippi",
+            "sippissim",
+            "aaaaaaaa",
+            "aaab",
+            "abcabcabcabd",
+            "bcabcab",
+        ];
+
+        for a in words.iter() {
+            for b in words.iter() {
+                let (a_chars, b_chars): (Vec<char>, Vec<char>) =
+                    (a.chars().collect(), b.chars().collect());
+                let want = brute_force(&a_chars, &b_chars);
+                match longest_common_substring(a, b) {
+                    None => assert_eq!(want, 0, "longest_common_substring({}, {})", a, b),
+                    Some(common) => {
+                        assert_eq!(common.len, want, "longest_common_substring({}, {})", a, b);
+                        let (x, y) = (common.starts[0], common.starts[1]);
+                        assert_eq!(
+                            a_chars[x..x + want],
+                            b_chars[y..y + want],
+                            "longest_common_substring({}, {})",
+                            a,
+                            b
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_finds_substrings_of_many() {
+        let inputs = ["xxabcdefyy", "abcdzzcdefg", "qcdefabcd"];
+        let common = longest_common_substring_many(&inputs).unwrap();
+        assert_eq!(
+            common,
+            CommonSubstring {
+                starts: vec![2, 0, 5],
+                len: 4
+            }
+        );
+
+        assert_eq!(longest_common_substring_many(&[]), None);
+        assert_eq!(
+            longest_common_substring_many(&["solo"]),
+            Some(CommonSubstring {
+                starts: vec![0],
+                len: 4
+            })
+        );
+        assert_eq!(
+            longest_common_substring_many(&["abc", "bcd", "cde", "xyz"]),
+            None
+        );
+
+        let common =
+            longest_common_substring_many_with(&["cafe\u{301}s", "un cafe\u{301}"], Unit::Grapheme);
+        assert_eq!(
+            common,
+            Some(CommonSubstring {
+                starts: vec![0, 3],
+                len: 4
+            })
+        );
+    }
+}
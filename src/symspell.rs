@@ -0,0 +1,386 @@
+// SymSpell finds dictionary terms close to a query using only deletions. If a and b are
+// within k edits then deleting at most k chars from each of them gives the same string,
+// since a substitution or a transposition is undone by one deletion on each side and an
+// insertion by one deletion on the other. So every string reachable from each term by up
+// to k deletions is indexed ahead of time, a lookup generates the same for the query, and
+// any term sharing one of those strings is a candidate, to be checked with the real
+// distance.
+//
+// To keep millions of terms in memory the deletions are stored as 64 bit hashes in one
+// sorted array, alongside a parallel array of term ids, rather than as strings in a hash
+// map; a hash collision only adds a candidate that fails the check. Terms live back to
+// back in a single string. Only deletions from the first prefix_len chars of each term
+// are indexed, which bounds the number per term however long it is: two words within k
+// edits still have prefixes within k deletions of a common string.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::{damerau_distance, distance_within};
+
+// DEFAULT_PREFIX_LEN is the prefix_len used by SymSpell::new
+const DEFAULT_PREFIX_LEN: usize = 7;
+
+/// SymSpell is a dictionary of terms and their frequencies for finding those within a
+/// few edits of a query, counting each char as one symbol
+///
+/// ```
+/// use lev_rs::SymSpell;
+///
+/// let dictionary = SymSpell::new(2, [("their", 900), ("there", 1200), ("three", 300)]);
+///
+/// // All three are two edits away, so the most frequent comes first
+/// let suggestions = dictionary.lookup("thier", 2);
+/// let terms: Vec<&str> = suggestions.iter().map(|s| s.term).collect();
+/// assert_eq!(terms, ["there", "their", "three"]);
+///
+/// // A transposition is a single edit under Damerau-Levenshtein
+/// assert_eq!(dictionary.lookup_damerau("thier", 1)[0].term, "their");
+/// ```
+pub struct SymSpell {
+    max: usize,
+    prefix_len: usize,
+    // Term i is text[ends[i - 1]..ends[i]], starting from 0 for the first
+    text: String,
+    ends: Vec<u32>,
+    frequencies: Vec<u64>,
+    // hashes is sorted, and ids[i] is the term that deletions hashing to hashes[i] came from
+    hashes: Vec<u64>,
+    ids: Vec<u32>,
+}
+
+/// Suggestion is a dictionary term found by a SymSpell lookup
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Suggestion<'t> {
+    pub term: &'t str,
+    pub distance: usize,
+    pub frequency: u64,
+}
+
+impl SymSpell {
+    /// New builds a dictionary allowing lookups of up to max edits from terms and their
+    /// frequencies. A term listed more than once has its frequencies added together.
+    /// Deletions are indexed from the first 7 chars of each term, or max + 1 if that's
+    /// more
+    pub fn new<S, I>(max: usize, terms: I) -> Self
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = (S, u64)>,
+    {
+        SymSpell::with_prefix_len(max, DEFAULT_PREFIX_LEN.max(max + 1), terms)
+    }
+
+    /// With_prefix_len builds a dictionary like new, indexing deletions from only the
+    /// first prefix_len chars of each term. Longer prefixes mean more memory but fewer
+    /// candidates to check on each lookup. A prefix_len of max or less is raised to
+    /// max + 1, since any shorter prefix can be deleted entirely, making every term a
+    /// candidate for every query
+    pub fn with_prefix_len<S, I>(max: usize, prefix_len: usize, terms: I) -> Self
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = (S, u64)>,
+    {
+        let prefix_len = prefix_len.max(max + 1);
+
+        let mut dictionary = SymSpell {
+            max,
+            prefix_len,
+            text: String::new(),
+            ends: Vec::new(),
+            frequencies: Vec::new(),
+            hashes: Vec::new(),
+            ids: Vec::new(),
+        };
+
+        // Sorting the terms brings any repeats of a term together, so they can be merged
+        // without a map from each term to its id
+        let mut terms: Vec<(S, u64)> = terms.into_iter().collect();
+        terms.sort_unstable_by(|a, b| a.0.as_ref().cmp(b.0.as_ref()));
+
+        let mut entries: Vec<(u64, u32)> = Vec::new();
+        let mut last: Option<&str> = None;
+        for (term, frequency) in terms.iter() {
+            let term = term.as_ref();
+            if last == Some(term) {
+                *dictionary.frequencies.last_mut().expect("last is a term") += frequency;
+                continue;
+            }
+            last = Some(term);
+
+            let id = offset(dictionary.frequencies.len());
+            dictionary.text.push_str(term);
+            dictionary.ends.push(offset(dictionary.text.len()));
+            dictionary.frequencies.push(*frequency);
+
+            let deletions = dictionary.deletions_within(term, max);
+            entries.extend(deletions.into_iter().map(|hash| (hash, id)));
+        }
+
+        entries.sort_unstable();
+        dictionary.hashes = entries.iter().map(|&(hash, _)| hash).collect();
+        dictionary.ids = entries.iter().map(|&(_, id)| id).collect();
+        dictionary
+    }
+
+    /// Max returns the largest number of edits a lookup can allow
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    pub fn len(&self) -> usize {
+        self.frequencies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frequencies.is_empty()
+    }
+
+    /// Lookup returns the terms within k Levenshtein edits of query, closest first, and
+    /// most frequent first among those at the same distance. k is capped at max
+    pub fn lookup(&self, query: &str, k: usize) -> Vec<Suggestion<'_>> {
+        let k = k.min(self.max);
+        self.suggest(query, k, |term| distance_within(query, term, k))
+    }
+
+    /// Lookup_damerau returns the terms within k Damerau-Levenshtein edits of query, so a
+    /// swap of neighbouring chars counts as one edit, ordered as lookup orders them
+    pub fn lookup_damerau(&self, query: &str, k: usize) -> Vec<Suggestion<'_>> {
+        let k = k.min(self.max);
+        self.suggest(query, k, |term| {
+            Some(damerau_distance(query, term)).filter(|&d| d <= k)
+        })
+    }
+
+    // suggest checks every candidate term for query with verify, which returns the
+    // distance to a term if it is at most k
+    fn suggest<'t>(
+        &'t self,
+        query: &str,
+        k: usize,
+        verify: impl Fn(&str) -> Option<usize>,
+    ) -> Vec<Suggestion<'t>> {
+        let mut candidates: Vec<u32> = Vec::new();
+        for hash in self.deletions_within(query, k) {
+            let start = self.hashes.partition_point(|&h| h < hash);
+            let end = self.hashes.partition_point(|&h| h <= hash);
+            candidates.extend_from_slice(&self.ids[start..end]);
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let query_len = query.chars().count();
+        let mut suggestions: Vec<Suggestion> = candidates
+            .into_iter()
+            .filter_map(|id| {
+                let term = self.term(id as usize);
+                if term.chars().count().abs_diff(query_len) > k {
+                    return None;
+                }
+                verify(term).map(|distance| Suggestion {
+                    term,
+                    distance,
+                    frequency: self.frequencies[id as usize],
+                })
+            })
+            .collect();
+
+        suggestions.sort_by(|a, b| {
+            (a.distance, b.frequency, a.term).cmp(&(b.distance, a.frequency, b.term))
+        });
+        suggestions
+    }
+
+    fn term(&self, id: usize) -> &str {
+        let start = if id == 0 {
+            0
+        } else {
+            self.ends[id - 1] as usize
+        };
+        &self.text[start..self.ends[id] as usize]
+    }
+
+    // deletions_within returns the distinct hashes of every string reachable from the
+    // prefix of word by deleting at most k chars
+    fn deletions_within(&self, word: &str, k: usize) -> Vec<u64> {
+        let prefix: Vec<char> = word.chars().take(self.prefix_len).collect();
+
+        let mut hashes = Vec::new();
+        push_deletions(
+            &prefix,
+            k,
+            &mut Vec::with_capacity(prefix.len()),
+            &mut hashes,
+        );
+        hashes.sort_unstable();
+        hashes.dedup();
+        hashes
+    }
+}
+
+// push_deletions pushes the hash of kept followed by each string reachable from rest by
+// deleting at most k chars. Each choice of positions to delete is visited once, reusing
+// kept as the buffer, so a string that can be reached in several ways is pushed more
+// than once
+fn push_deletions(rest: &[char], k: usize, kept: &mut Vec<char>, hashes: &mut Vec<u64>) {
+    let Some((&first, rest)) = rest.split_first().filter(|_| k > 0) else {
+        let len = kept.len();
+        kept.extend_from_slice(rest);
+        hashes.push(hash(kept));
+        kept.truncate(len);
+        return;
+    };
+
+    kept.push(first);
+    push_deletions(rest, k, kept, hashes);
+    kept.pop();
+    push_deletions(rest, k - 1, kept, hashes);
+}
+
+fn hash(word: &[char]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    word.hash(&mut hasher);
+    hasher.finish()
+}
+
+// offset narrows a term offset or id to the u32 the index stores it as
+fn offset(n: usize) -> u32 {
+    u32::try_from(n).expect("SymSpell holds at most 4 GiB of terms")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: [&str; 24] = [
+        "a",
+        "ab",
+        "ba",
+        "abc",
+        "acb",
+        "fast",
+        "past",
+        "pasta",
+        "last",
+        "kitten",
+        "sitting",
+        "mitten",
+        "kitchen",
+        "written",
+        "knitting",
+        "intention",
+        "execution",
+        "naïve",
+        "naive",
+        "abcdefghij",
+        "abcdefghxy",
+        "xbcdefghij",
+        "bcdefghijk",
+        "abcdefghijklmnop",
+    ];
+
+    #[test]
+    fn it_agrees_with_brute_force() {
+        let terms = WORDS.iter().enumerate().map(|(i, w)| (*w, i as u64));
+        let queries = [
+            "",
+            "a",
+            "abd",
+            "fsat",
+            "kiten",
+            "sittign",
+            "naïv",
+            "bcdefghij",
+            "abcdefghijklmnp",
+            "zzz",
+        ];
+
+        for (max, prefix_len) in [(1, 2), (2, 3), (2, 7), (3, 100)] {
+            let dictionary = SymSpell::with_prefix_len(max, prefix_len, terms.clone());
+            for query in queries.iter() {
+                for k in 0..=max {
+                    let mut want: Vec<(&str, usize)> = WORDS
+                        .iter()
+                        .filter_map(|w| distance_within(query, w, k).map(|d| (*w, d)))
+                        .collect();
+                    want.sort();
+
+                    let mut got: Vec<(&str, usize)> = dictionary
+                        .lookup(query, k)
+                        .iter()
+                        .map(|s| (s.term, s.distance))
+                        .collect();
+                    got.sort();
+                    assert_eq!(
+                        got, want,
+                        "lookup({}, {}) with max {} and prefix_len {} - got {:?}, want {:?}",
+                        query, k, max, prefix_len, got, want
+                    );
+
+                    let mut want: Vec<(&str, usize)> = WORDS
+                        .iter()
+                        .map(|w| (*w, damerau_distance(query, w)))
+                        .filter(|&(_, d)| d <= k)
+                        .collect();
+                    want.sort();
+
+                    let mut got: Vec<(&str, usize)> = dictionary
+                        .lookup_damerau(query, k)
+                        .iter()
+                        .map(|s| (s.term, s.distance))
+                        .collect();
+                    got.sort();
+                    assert_eq!(
+                        got, want,
+                        "lookup_damerau({}, {}) with max {} and prefix_len {} - got {:?}, want {:?}",
+                        query, k, max, prefix_len, got, want
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_raises_short_prefixes() {
+        let dictionary = SymSpell::new(7, [("abc", 1)]);
+        assert_eq!(dictionary.prefix_len, 8);
+        assert_eq!(dictionary.lookup("xyz", 3)[0].term, "abc");
+
+        let dictionary = SymSpell::with_prefix_len(2, 1, [("abc", 1)]);
+        assert_eq!(dictionary.prefix_len, 3);
+        assert_eq!(dictionary.lookup("abd", 1)[0].term, "abc");
+    }
+
+    #[test]
+    fn it_ranks_by_frequency() {
+        let dictionary = SymSpell::new(
+            2,
+            [
+                ("cat", 10),
+                ("cart", 50),
+                ("cast", 5),
+                ("car", 100),
+                ("cat", 45),
+                ("cut", 1),
+            ],
+        );
+        assert_eq!(dictionary.len(), 5);
+
+        let got: Vec<(&str, usize, u64)> = dictionary
+            .lookup("cat", 1)
+            .iter()
+            .map(|s| (s.term, s.distance, s.frequency))
+            .collect();
+        assert_eq!(
+            got,
+            [
+                ("cat", 0, 55),
+                ("car", 1, 100),
+                ("cart", 1, 50),
+                ("cast", 1, 5),
+                ("cut", 1, 1)
+            ]
+        );
+
+        assert!(dictionary.lookup("dog", 5).is_empty());
+    }
+}
@@ -0,0 +1,232 @@
+// A trie stores words by their chars, one node per distinct prefix. Searching it walks
+// the nodes depth first carrying the single_row_distance row for the query against the
+// prefix spelled out so far, so each row is computed once and shared by every word below
+// that node, rather than once per word. A row whose minimum is over k means every word
+// below it is too, since extending b never lowers the best cell in a row, so the walk
+// skips that whole subtree.
+
+use crate::single_row_step;
+
+/// Trie is a dictionary of words that can be searched for every word within k edits of
+/// a query, counting each char as one symbol
+///
+/// ```
+/// use lev_rs::Trie;
+///
+/// let trie: Trie = ["cat", "cart", "care", "dog", "cater"].into_iter().collect();
+/// assert_eq!(
+///     trie.search("cat", 1),
+///     [("cat".to_string(), 0), ("cart".to_string(), 1)]
+/// );
+/// ```
+pub struct Trie {
+    nodes: Vec<Node>,
+    len: usize,
+}
+
+// Node is a prefix in the trie. children are sorted by char, so words are visited in
+// order, and terminal marks a prefix that is itself a word
+#[derive(Default)]
+struct Node {
+    children: Vec<(char, usize)>,
+    terminal: bool,
+}
+
+impl Trie {
+    pub fn new() -> Self {
+        Trie {
+            nodes: vec![Node::default()],
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert adds word to the trie, returning false if it was already there
+    pub fn insert(&mut self, word: &str) -> bool {
+        let mut i = 0;
+        for c in word.chars() {
+            i = match self.nodes[i].children.binary_search_by_key(&c, |&(c, _)| c) {
+                Ok(found) => self.nodes[i].children[found].1,
+                Err(at) => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::default());
+                    self.nodes[i].children.insert(at, (c, child));
+                    child
+                }
+            };
+        }
+
+        let added = !self.nodes[i].terminal;
+        self.nodes[i].terminal = true;
+        self.len += usize::from(added);
+        added
+    }
+
+    /// Contains returns whether word is in the trie
+    pub fn contains(&self, word: &str) -> bool {
+        let mut i = 0;
+        for c in word.chars() {
+            let node = &self.nodes[i];
+            match node.children.binary_search_by_key(&c, |&(c, _)| c) {
+                Ok(found) => i = node.children[found].1,
+                Err(_) => return false,
+            }
+        }
+        self.nodes[i].terminal
+    }
+
+    /// Search returns every word within k edits of query along with its distance,
+    /// closest first, and in alphabetical order among words at the same distance
+    pub fn search(&self, query: &str, k: usize) -> Vec<(String, usize)> {
+        let mut found = Vec::new();
+        let query: Vec<char> = query.chars().collect();
+        let over = k + 1;
+        let mut walk = Walk {
+            trie: self,
+            query: &query,
+            k,
+            word: String::new(),
+            rows: vec![(0..=query.len()).map(|x| x.min(over)).collect()],
+            found: &mut found,
+        };
+        walk.visit(0, 0);
+
+        found.sort_by_key(|&(_, d)| d);
+        found
+    }
+}
+
+// Walk is the state of a depth first search: word is the prefix spelled out by the path
+// to the current node, and rows[depth] is the DP row for the first depth chars of it
+struct Walk<'t> {
+    trie: &'t Trie,
+    query: &'t [char],
+    k: usize,
+    word: String,
+    rows: Vec<Vec<usize>>,
+    found: &'t mut Vec<(String, usize)>,
+}
+
+impl Walk<'_> {
+    fn visit(&mut self, i: usize, depth: usize) {
+        let node = &self.trie.nodes[i];
+
+        let d = self.rows[depth][self.query.len()];
+        if node.terminal && d <= self.k {
+            self.found.push((self.word.clone(), d));
+        }
+
+        for &(c, child) in node.children.iter() {
+            // Rows deeper than this one are left over from earlier branches, so reuse them
+            if self.rows.len() == depth + 1 {
+                self.rows.push(Vec::with_capacity(self.query.len() + 1));
+            }
+            let (above, below) = self.rows.split_at_mut(depth + 1);
+            single_row_step(&above[depth], self.query, &c, self.k + 1, &mut below[0]);
+
+            if below[0].iter().min().is_some_and(|&min| min <= self.k) {
+                self.word.push(c);
+                self.visit(child, depth + 1);
+                self.word.pop();
+            }
+        }
+    }
+}
+
+impl Default for Trie {
+    fn default() -> Self {
+        Trie::new()
+    }
+}
+
+impl<'w> Extend<&'w str> for Trie {
+    fn extend<I: IntoIterator<Item = &'w str>>(&mut self, words: I) {
+        for word in words {
+            self.insert(word);
+        }
+    }
+}
+
+impl<'w> FromIterator<&'w str> for Trie {
+    fn from_iter<I: IntoIterator<Item = &'w str>>(words: I) -> Self {
+        let mut trie = Trie::new();
+        trie.extend(words);
+        trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: [&str; 20] = [
+        "",
+        "a",
+        "ab",
+        "ba",
+        "abc",
+        "fast",
+        "past",
+        "pasta",
+        "last",
+        "kitten",
+        "kit",
+        "kite",
+        "sitting",
+        "mitten",
+        "kitchen",
+        "knitting",
+        "intention",
+        "execution",
+        "naïve",
+        "naive",
+    ];
+
+    #[test]
+    fn it_finds_words_within() {
+        let trie: Trie = WORDS.into_iter().collect();
+        assert_eq!(trie.len(), WORDS.len());
+
+        for query in ["", "a", "kitten", "past", "naïv", "xyz", "intentions"] {
+            for k in 0..=4 {
+                let mut want: Vec<(String, usize)> = WORDS
+                    .iter()
+                    .filter_map(|w| crate::distance_within(query, w, k).map(|d| (w.to_string(), d)))
+                    .collect();
+                want.sort_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+
+                let got = trie.search(query, k);
+                assert_eq!(
+                    got, want,
+                    "search({}, {}) - got {:?}, want {:?}",
+                    query, k, got, want
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_inserts_words() {
+        let mut trie = Trie::default();
+        assert!(trie.is_empty());
+        assert!(!trie.contains(""));
+        assert!(trie.search("a", 3).is_empty());
+
+        assert!(trie.insert("kitten"));
+        assert!(trie.insert("kit"));
+        assert!(!trie.insert("kitten"));
+        assert_eq!(trie.len(), 2);
+
+        assert!(trie.contains("kit"));
+        assert!(trie.contains("kitten"));
+        assert!(!trie.contains("kitt"));
+        assert!(!trie.contains("kittens"));
+    }
+}
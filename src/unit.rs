@@ -0,0 +1,36 @@
+/// Unit selects what counts as a single symbol when comparing two strings
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Unit {
+    /// Raw UTF-8 bytes. Fastest, but a single accented letter may count as several edits
+    Byte,
+    /// Unicode scalar values, as produced by `str::chars`
+    #[default]
+    Char,
+    /// Extended grapheme clusters, i.e. user-perceived characters such as "e\u{301}" or 👩‍👧
+    Grapheme,
+}
+
+// with_units splits the strings $a and $b into slices of the given unit, binds them to
+// $x and $y and evaluates $body once for whichever unit was selected
+macro_rules! with_units {
+    ($unit:expr, $a:expr, $b:expr, |$x:ident, $y:ident| $body:expr) => {
+        match $unit {
+            $crate::Unit::Byte => {
+                let ($x, $y) = ($a.as_bytes(), $b.as_bytes());
+                $body
+            }
+            $crate::Unit::Char => {
+                let $x: Vec<char> = $a.chars().collect();
+                let $y: Vec<char> = $b.chars().collect();
+                let ($x, $y) = (&$x[..], &$y[..]);
+                $body
+            }
+            $crate::Unit::Grapheme => {
+                let $x: Vec<&str> = $crate::graphemes($a).collect();
+                let $y: Vec<&str> = $crate::graphemes($b).collect();
+                let ($x, $y) = (&$x[..], &$y[..]);
+                $body
+            }
+        }
+    };
+}